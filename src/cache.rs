@@ -0,0 +1,72 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+static CACHE_DIR: &str = "cache";
+
+// A previously fetched page, along with the validators needed to revalidate it.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CachedPage {
+    // Checked on load, so a page is never revalidated with another page's validators.
+    pub(crate) url: String,
+    pub(crate) etag: Option<String>,
+    pub(crate) last_modified: Option<String>,
+    pub(crate) body: String
+}
+
+// Entries are named by a hash of the URL, since URLs can be longer than a file name allows.
+fn path(url: &str) -> PathBuf {
+    PathBuf::from(CACHE_DIR).join(format!("{:016x}.json", fnv1a(url)))
+}
+
+// std's DefaultHasher may change between Rust releases, which would orphan every entry.
+fn fnv1a(string: &str) -> u64 {
+    string.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3))
+}
+
+pub(crate) fn load(url: &str) -> Option<CachedPage> {
+    let contents = fs::read_to_string(path(url)).ok()?;
+
+    match serde_json::from_str::<CachedPage>(&contents) {
+        Ok(page) if page.url == url => Some(page),
+        Ok(page) => {
+            warn!("Ignoring cache entry for {} stored under the same name as {}", page.url, url);
+            None
+        },
+        Err(e) => {
+            warn!("Ignoring unreadable cache entry for {}: {}", url, e);
+            None
+        }
+    }
+}
+
+pub(crate) fn store(url: &str, page: &CachedPage) -> Result<()> {
+    fs::create_dir_all(CACHE_DIR)?;
+    fs::write(path(url), serde_json::to_string(page)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_tells_apart_urls_that_differ_in_punctuation() {
+        assert_ne!(path("https://carfolio.com/specifications/models/car/?car=1_2"), path("https://carfolio.com/specifications/models/car/?car=1/2"));
+    }
+
+    #[test]
+    fn path_stays_short_for_long_urls() {
+        let url = format!("https://carfolio.com/specifications/models/?{}", "man=ford&".repeat(100));
+        assert_eq!(path(&url).file_name().unwrap().len(), "0123456789abcdef.json".len());
+    }
+
+    #[test]
+    fn fnv1a_matches_the_reference_values() {
+        assert_eq!(fnv1a(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a("a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Make {
+    pub(crate) name: String,
+    pub(crate) country: String,
+    pub(crate) url: String
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct ModelListing {
+    pub(crate) make: String,
+    pub(crate) name: Option<String>,
+    pub(crate) year: Option<String>,
+    pub(crate) markets: BTreeSet<String>,
+    pub(crate) url: String,
+    // The vehicle page answered 404 when it was fetched.
+    pub(crate) gone: bool
+}
+
+// Make names in the current grid that weren't in the previous one.
+pub(crate) fn new_makes<'a>(previous: &[String], current: &'a [String]) -> Vec<&'a String> {
+    current.iter().filter(|name| !previous.contains(name)).collect()
+}
+
+// Normalises a URL so the same page is recognised however it was linked:
+// https, no "www.", a lowercase host and no fragment.
+pub(crate) fn normalize_url(url: &str) -> String {
+    let mut parsed = match reqwest::Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(e)     => {
+            warn!("Unable to normalize URL {}: {}", url, e);
+            return url.to_string();
+        }
+    };
+
+    let host = parsed.host_str().map(|host| host.to_lowercase().trim_start_matches("www.").to_string());
+
+    if parsed.set_host(host.as_deref()).is_err() || parsed.set_scheme("https").is_err() {
+        warn!("Unable to normalize URL {}", url);
+        return url.to_string();
+    }
+
+    parsed.set_fragment(None);
+    parsed.to_string()
+}
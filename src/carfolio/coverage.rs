@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::vehicle::{Outcome, Vehicle};
+
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct FieldCoverage {
+    parsed: u32,
+    inconsistent: u32,
+    missing: u32,
+    unparseable: u32,
+    ignored: u32,
+    success_rate: f32,
+    missing_rate: f32,
+    failure_rate: f32
+}
+
+impl FieldCoverage {
+    fn record(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Parsed       => self.parsed += 1,
+            Outcome::Inconsistent => self.inconsistent += 1,
+            Outcome::Missing      => self.missing += 1,
+            Outcome::Unparseable  => self.unparseable += 1,
+            Outcome::Ignored      => self.ignored += 1
+        }
+
+        let total = (self.parsed + self.inconsistent + self.missing + self.unparseable + self.ignored) as f32;
+        self.success_rate = self.parsed as f32 / total;
+        self.missing_rate = self.missing as f32 / total;
+        self.failure_rate = self.unparseable as f32 / total;
+    }
+}
+
+// Parse outcomes for every field across a crawl, broken down per make and per model year.
+// Every parsed vehicle counts, including the ones strict mode rejects or that turn out to be duplicates.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct CoverageReport {
+    vehicles: u32,
+    // Vehicle pages that couldn't be parsed at all.
+    failed: u32,
+    fields: BTreeMap<String, FieldCoverage>,
+    by_make: BTreeMap<String, BTreeMap<String, FieldCoverage>>,
+    by_year: BTreeMap<String, BTreeMap<String, FieldCoverage>>,
+    unused_keys: BTreeMap<String, u32>
+}
+
+impl CoverageReport {
+    pub(super) fn record(&mut self, vehicle: &Vehicle) {
+        self.vehicles += 1;
+
+        for (key, provenance) in vehicle.provenance() {
+            let outcome = provenance.outcome();
+
+            self.fields.entry(key.clone()).or_default().record(outcome);
+            self.by_make.entry(vehicle.make().to_string()).or_default()
+                .entry(key.clone()).or_default().record(outcome);
+            self.by_year.entry(vehicle.year().to_string()).or_default()
+                .entry(key.clone()).or_default().record(outcome);
+        }
+
+        for key in vehicle.unused_keys() {
+            *self.unused_keys.entry(key.clone()).or_default() += 1;
+        }
+    }
+
+    pub(super) fn record_failure(&mut self) {
+        self.failed += 1;
+    }
+
+    pub(super) fn log_summary(&self) {
+        if self.failed > 0 {
+            info!("{} of {} vehicle pages couldn't be parsed", self.failed, self.vehicles + self.failed);
+        }
+
+        for (key, coverage) in &self.fields {
+            if coverage.unparseable > 0 {
+                info!("{} unparseable on {:.1}% of vehicles", key, coverage.failure_rate * 100.0);
+            }
+            if coverage.inconsistent > 0 {
+                info!("{} inconsistent on {} vehicles", key, coverage.inconsistent);
+            }
+        }
+    }
+}
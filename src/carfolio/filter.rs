@@ -0,0 +1,99 @@
+use regex::Regex;
+
+use crate::error::Result;
+
+// The makes crawled when no allow list is given.
+lazy_static! {
+    static ref DEFAULT_MAKES: Vec<&'static str> = vec![
+        "Acura",
+        "Alfa Romeo",
+        "Ariel",
+        "Aston Martin",
+        "Audi",
+        "BAC",
+        "BMW",
+        "Bugatti",
+        "Buick",
+        "Cadillac",
+        "Caterham",
+        "Chevrolet",
+        "Chrysler",
+        "Dodge",
+        "Ferrari",
+        "Fiat",
+        "Ford",
+        "Honda",
+        "Hyundai",
+        "Infiniti",
+        "Jaguar",
+        "Jeep",
+        "Kia",
+        "Koenigsegg",
+        "Lamborghini",
+        "Land Rover",
+        "Lexus",
+        "Lincoln",
+        "Lotus",
+        "Maserati",
+        "Mazda",
+        "McLaren",
+        "Mercedes-Benz",
+        "MINI",
+        "Mitsubishi",
+        "Nissan",
+        "Oldsmobile",
+        "Pagani",
+        "Plymouth",
+        "Polestar",
+        "Pontiac",
+        "Porsche",
+        "Rolls-Royce",
+        "Saab",
+        "Saturn",
+        "Scion",
+        "Shelby",
+        "Shelby Super Cars",
+        "smart",
+        "Subaru",
+        "Suzuki",
+        "Tesla",
+        "Toyota",
+        "Volkswagen",
+        "Volvo",
+        "Zenvo"
+    ];
+}
+
+// Decides which discovered makes get crawled. A make is crawled when it matches any allow
+// pattern (or the default preset, if there are none) and no deny pattern.
+pub(crate) struct MakeFilter {
+    allow: Vec<Regex>,
+    deny: Vec<Regex>
+}
+
+impl MakeFilter {
+    pub(crate) fn new(allow: &[String], deny: &[String]) -> Result<MakeFilter> {
+        let allow = if allow.is_empty() {
+            DEFAULT_MAKES.iter().map(|make| Regex::new(&format!("^{}$", regex::escape(make)))).collect::<std::result::Result<_, _>>()?
+        } else {
+            allow.iter().map(|pattern| pattern_regex(pattern)).collect::<Result<_>>()?
+        };
+        let deny = deny.iter().map(|pattern| pattern_regex(pattern)).collect::<Result<_>>()?;
+
+        Ok(MakeFilter { allow, deny })
+    }
+
+    pub(crate) fn matches(&self, make: &str) -> bool {
+        self.allow.iter().any(|re| re.is_match(make)) && !self.deny.iter().any(|re| re.is_match(make))
+    }
+}
+
+// Patterns prefixed with "regex:" are regular expressions; anything else is a case-insensitive glob.
+fn pattern_regex(pattern: &str) -> Result<Regex> {
+    if let Some(re) = pattern.strip_prefix("regex:") {
+        return Ok(Regex::new(re)?);
+    }
+
+    let glob = regex::escape(pattern).replace(r"\*", ".*").replace(r"\?", ".");
+    Ok(Regex::new(&format!("(?i)^{}$", glob))?)
+}
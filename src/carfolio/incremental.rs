@@ -0,0 +1,158 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::Result;
+
+use super::{Crawl, ModelListing};
+
+// What a previous run already scraped, keyed the same way a fresh crawl discovers it.
+pub(crate) struct Incremental {
+    pub(super) known_urls: BTreeSet<String>,
+    pub(super) known_ids: BTreeMap<String, String>,
+    // Fraction of already-known listings to fetch again, to catch pages that changed.
+    pub(super) reverify_rate: f64
+}
+
+#[derive(Serialize)]
+pub(crate) struct Delta {
+    added_listings: Vec<ModelListing>,
+    added_vehicles: Vec<Value>,
+    changed_vehicles: Vec<Value>,
+    gone_vehicles: Vec<String>
+}
+
+impl Incremental {
+    pub(crate) fn new(previous: &[Value], reverify_rate: f64) -> Incremental {
+        let mut known_urls = BTreeSet::new();
+        let mut known_ids = BTreeMap::new();
+
+        for vehicle in previous {
+            if let Some(url) = vehicle["url"].as_str() {
+                known_urls.insert(url.to_string());
+
+                if let Some(id) = vehicle["carfolio_id"].as_str() {
+                    known_ids.insert(id.to_string(), url.to_string());
+                }
+            }
+        }
+
+        info!("Incremental crawl against {} known vehicles", known_urls.len());
+
+        Incremental { known_urls, known_ids, reverify_rate }
+    }
+
+    pub(super) fn should_fetch(&self, listing: &ModelListing) -> bool {
+        if !self.known_urls.contains(&listing.url) {
+            return true;
+        }
+
+        if rand::random::<f64>() < self.reverify_rate {
+            info!("Re-verifying known listing: {}", listing.url);
+            return true;
+        }
+
+        false
+    }
+}
+
+// Folds a crawl into the previous dataset. Re-verified vehicles replace their old record,
+// and anything that wasn't there before is reported in the delta.
+pub(crate) fn merge(previous: Vec<Value>, crawl: &Crawl) -> Result<(Vec<Value>, Delta)> {
+    let known_urls: BTreeSet<String> = previous.iter()
+        .filter_map(|vehicle| vehicle["url"].as_str().map(String::from))
+        .collect();
+
+    let mut dataset = previous;
+    let mut delta = Delta {
+        added_listings: crawl.listings.iter().filter(|listing| !known_urls.contains(&listing.url)).cloned().collect(),
+        added_vehicles: vec![],
+        changed_vehicles: vec![],
+        gone_vehicles: vec![]
+    };
+
+    // Known vehicles whose page now answers 404 are dropped from the dataset.
+    for listing in crawl.listings.iter().filter(|listing| listing.gone && known_urls.contains(&listing.url)) {
+        warn!("Removing vehicle whose page is gone: {}", listing.url);
+        delta.gone_vehicles.push(listing.url.clone());
+    }
+    if !delta.gone_vehicles.is_empty() {
+        let gone: BTreeSet<&str> = delta.gone_vehicles.iter().map(String::as_str).collect();
+        dataset.retain(|known| known["url"].as_str().is_none_or(|url| !gone.contains(url)));
+    }
+
+    let mut indices: BTreeMap<String, usize> = dataset.iter().enumerate()
+        .filter_map(|(index, known)| Some((known["url"].as_str()?.to_string(), index)))
+        .collect();
+
+    for vehicle in &crawl.vehicles {
+        let value = serde_json::to_value(vehicle)?;
+
+        match indices.get(vehicle.url()) {
+            Some(&index) => {
+                if dataset[index] != value {
+                    warn!("Known vehicle has changed since the previous run: {}", vehicle.url());
+                    delta.changed_vehicles.push(value.clone());
+                    dataset[index] = value;
+                }
+            },
+            None => {
+                indices.insert(vehicle.url().to_string(), dataset.len());
+                delta.added_vehicles.push(value.clone());
+                dataset.push(value);
+            }
+        }
+    }
+
+    info!("Incremental crawl added {} listings and {} vehicles, and found {} changed and {} gone vehicles",
+          delta.added_listings.len(), delta.added_vehicles.len(), delta.changed_vehicles.len(), delta.gone_vehicles.len());
+
+    Ok((dataset, delta))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{CoverageReport, Vehicle};
+    use crate::Page;
+
+    fn vehicle(url: &str, model: &str) -> Vehicle {
+        let html = format!(r#"
+            <div><h3><span class="automobile"><span class="Year">2019</span> <span class="manufacturer">Ford</span> <span class="model name">{}</span></span></h3></div>
+            <table class="specstable"><tbody></tbody></table>
+        "#, model);
+        Vehicle::new(Page::from_html(url, &html), BTreeSet::new()).unwrap()
+    }
+
+    fn listing(url: &str, gone: bool) -> ModelListing {
+        ModelListing { make: String::from("Ford"), name: None, year: None, markets: BTreeSet::new(), url: url.to_string(), gone }
+    }
+
+    fn urls(values: &[Value]) -> Vec<&str> {
+        values.iter().map(|value| value["url"].as_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn merge_reports_added_changed_and_gone_vehicles() {
+        let previous: Vec<Value> = [("a", "Focus"), ("b", "Fiesta"), ("c", "Puma")].iter()
+            .map(|(url, model)| serde_json::to_value(vehicle(url, model)).unwrap())
+            .collect();
+        let crawl = Crawl {
+            discovered_makes: vec![String::from("Ford")],
+            makes: vec![],
+            listings: vec![listing("a", false), listing("b", true), listing("c", false), listing("d", false)],
+            vehicles: vec![vehicle("a", "Focus ST"), vehicle("c", "Puma"), vehicle("d", "Kuga")],
+            coverage: CoverageReport::default()
+        };
+
+        let (dataset, delta) = merge(previous, &crawl).unwrap();
+
+        assert_eq!(urls(&dataset), vec!["a", "c", "d"]);
+        assert_eq!(dataset[0]["model"], "Focus ST");
+        assert_eq!(delta.added_listings, vec![listing("d", false)]);
+        assert_eq!(urls(&delta.added_vehicles), vec!["d"]);
+        assert_eq!(urls(&delta.changed_vehicles), vec!["a"]);
+        assert_eq!(delta.gone_vehicles, vec!["b"]);
+    }
+}
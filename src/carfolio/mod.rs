@@ -1,96 +1,192 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
 use logging_timer::time;
+use reqwest::StatusCode;
+use scraper::Selector;
 use scraper::element_ref::ElementRef;
 
-use crate::error::Result;
-use crate::{element_attr, element_within, inner_html};
+use crate::error::{Error, Result};
+use crate::{element_attr, element_within, elements_within, inner_html};
 use crate::Page;
 
+mod catalog;
+mod coverage;
+mod filter;
+mod incremental;
+mod robots;
 mod vehicle;
 
-use vehicle::Vehicle;
+use catalog::normalize_url;
+use coverage::CoverageReport;
+use robots::Robots;
+use vehicle::{Severity, Vehicle};
+
+pub(crate) use catalog::{Make, ModelListing, new_makes};
+pub(crate) use filter::MakeFilter;
+pub(crate) use incremental::{Incremental, merge as merge_incremental};
+pub(crate) use vehicle::supported_fields;
 
 static BASE_URL: &str = "https://carfolio.com";
 lazy_static! {
-    static ref MAKES: std::collections::BTreeSet<&'static str> = [
-        "Acura",
-        "Alfa Romeo",
-        "Ariel",
-        "Aston Martin",
-        "Audi",
-        "BAC",
-        "BMW",
-        "Bugatti",
-        "Buick",
-        "Cadillac",
-        "Caterham",
-        "Chevrolet",
-        "Chrysler",
-        "Dodge",
-        "Ferrari",
-        "Fiat",
-        "Ford",
-        "Honda",
-        "Hyundai",
-        "Infiniti",
-        "Jaguar",
-        "Jeep",
-        "Kia",
-        "Koenigsegg",
-        "Lamborghini",
-        "Land Rover",
-        "Lexus",
-        "Lincoln",
-        "Lotus",
-        "Maserati",
-        "Mazda",
-        "McLaren",
-        "Mercedes-Benz",
-        "MINI",
-        "Mitsubishi",
-        "Nissan",
-        "Oldsmobile",
-        "Pagani",
-        "Plymouth",
-        "Polestar",
-        "Pontiac",
-        "Porsche",
-        "Rolls-Royce",
-        "Saab",
-        "Saturn",
-        "Scion",
-        "Shelby",
-        "Shelby Super Cars",
-        "smart",
-        "Subaru",
-        "Suzuki",
-        "Tesla",
-        "Toyota",
-        "Volkswagen",
-        "Volvo",
-        "Zenvo"
-    ].iter().cloned().collect();
+    static ref PAGINATION_SELECTOR: Selector = Selector::parse("ul.pagination a, div.pagination a, nav.pagination a, a[rel=\"next\"]").unwrap();
+}
+
+pub(crate) struct Options {
+    // Reject vehicles that fail an error-level validation rule.
+    pub(crate) strict: bool,
+    // Only crawl makes that pass the allow and deny lists.
+    pub(crate) makes: MakeFilter,
+    // Keep listings tagged with any of these markets.
+    pub(crate) markets: BTreeSet<String>,
+    // Keep listings whose model year falls within this inclusive range.
+    pub(crate) from_year: Option<u16>,
+    pub(crate) to_year: Option<u16>,
+    // Only collect makes and model listings, without fetching vehicle pages.
+    pub(crate) catalog_only: bool,
+    // Skip listings a previous run already scraped.
+    pub(crate) incremental: Option<Incremental>
+}
+
+pub(crate) struct Crawl {
+    // Every make name in the specifications grid, whether or not it was crawled.
+    pub(crate) discovered_makes: Vec<String>,
+    pub(crate) makes: Vec<Make>,
+    pub(crate) listings: Vec<ModelListing>,
+    pub(crate) vehicles: Vec<Vehicle>,
+    pub(crate) coverage: CoverageReport
 }
 
 #[time("info")]
-pub(crate) fn scrape() -> Result<Vec<Vehicle>> {
-    let makes_page = Page::new(&format!("{}/specifications", BASE_URL));
+pub(crate) fn scrape(options: &Options) -> Result<Crawl> {
+    let robots = Robots::fetch();
+
+    let makes_url = format!("{}/specifications", BASE_URL);
+    let makes_page = match robots.fetch_page(&makes_url)? {
+        Some(page) => page,
+        None       => {
+            warn!("Nothing to crawl, since robots.txt disallows the makes page: {}", makes_url);
+            return Ok(Crawl { discovered_makes: vec![], makes: vec![], listings: vec![], vehicles: vec![], coverage: CoverageReport::default() });
+        }
+    };
 
-    let make_links = make_links(makes_page)?;
+    let (discovered_makes, makes) = makes(makes_page, &options.makes);
 
+    let mut listings: Vec<ModelListing> = vec![];
+    let mut listing_indices: BTreeMap<String, usize> = BTreeMap::new();
     let mut vehicles = vec![];
+    let mut coverage = CoverageReport::default();
+    let mut carfolio_ids: BTreeMap<String, String> = match &options.incremental {
+        Some(incremental) => incremental.known_ids.clone(),
+        None              => BTreeMap::new()
+    };
 
-    for link in &make_links {
-        let make_page = Page::new(link);
-
-        let model_links = model_links(make_page)?;
+    for make in &makes {
+        info!("Crawling Make: {}", make.name);
+        let make_page = match robots.fetch_page(&make.url).or_else(|e| skip_page("Make page", &make.url, e))? {
+            Some(page) => page,
+            None       => continue
+        };
 
-        for link in &model_links {
-            let model_page = Page::new(link);
-            vehicles.push(Vehicle::new(model_page)?);
+        // Rebadged models can be listed under several makes, so later listings of a page are merged into the first.
+        for listing in model_listings(make_page, options, |url| robots.fetch_page(url))? {
+            match listing_indices.get(&listing.url) {
+                Some(&index) => {
+                    let known = &mut listings[index];
+                    warn!("Duplicate listing under {} already listed under {}: {}", listing.make, known.make, listing.url);
+                    known.markets.extend(listing.markets);
+                },
+                None => {
+                    listing_indices.insert(listing.url.clone(), listings.len());
+                    listings.push(listing);
+                }
+            }
         }
     };
 
-    Ok(vehicles)
+    // Vehicles are only fetched once every make is listed, so each one carries all of its listing's markets.
+    if !options.catalog_only {
+        for listing in listings.iter_mut() {
+            if let Some(incremental) = &options.incremental {
+                if !incremental.should_fetch(listing) {
+                    continue;
+                }
+            }
+
+            let model_page = match robots.fetch_page(&listing.url) {
+                Ok(Some(page)) => page,
+                Ok(None)       => continue,
+                Err(Error::HttpError { status: StatusCode::NOT_FOUND, .. }) => {
+                    warn!("Vehicle page is gone: {}", listing.url);
+                    listing.gone = true;
+                    continue;
+                },
+                Err(e) => {
+                    skip_page("vehicle page", &listing.url, e)?;
+                    continue;
+                }
+            };
+
+            // A 304 for a page the previous run already parsed means its record is still current.
+            if model_page.not_modified && options.incremental.as_ref().is_some_and(|incremental| incremental.known_urls.contains(&listing.url)) {
+                info!("Skipping unchanged vehicle page: {}", listing.url);
+                continue;
+            }
+
+            let vehicle = match Vehicle::new(model_page, listing.markets.clone()) {
+                Ok(vehicle) => vehicle,
+                Err(e)      => {
+                    warn!("Skipping vehicle page that couldn't be parsed: {}: {}", listing.url, e);
+                    coverage.record_failure();
+                    continue;
+                }
+            };
+            coverage.record(&vehicle);
+
+            if options.strict && vehicle.has_violations(Severity::Error) {
+                warn!("Rejecting vehicle that failed validation in strict mode: {}", listing.url);
+                continue;
+            }
+
+            if let Some(id) = vehicle.carfolio_id() {
+                if let Some(url) = carfolio_ids.get(id).filter(|url| **url != listing.url) {
+                    warn!("Duplicate vehicle with carfolio.com ID {} at {}, already scraped from {}", id, listing.url, url);
+                    continue;
+                }
+
+                carfolio_ids.insert(id.to_string(), listing.url.clone());
+            }
+
+            vehicles.push(vehicle);
+        }
+    }
+
+    coverage.log_summary();
+
+    Ok(Crawl { discovered_makes, makes, listings, vehicles, coverage })
+}
+
+// Errors that only lose the one page, so the rest of the crawl carries on without it.
+fn skip_page(kind: &str, url: &str, error: Error) -> Result<Option<Page>> {
+    match error {
+        Error::HttpError { status, .. } => {
+            warn!("Skipping {} that answered HTTP {}: {}", kind, status, url);
+            Ok(None)
+        },
+        Error::NotArchived { .. } => {
+            warn!("Skipping {} missing from the WARC archive: {}", kind, url);
+            Ok(None)
+        },
+        Error::ReadTimeout { .. } => {
+            warn!("Skipping {} that stopped sending data: {}", kind, url);
+            Ok(None)
+        },
+        // Timeouts, dropped connections and truncated bodies, as opposed to a request that could never be built.
+        Error::ReqwestError(e) if e.is_timeout() || e.is_request() || e.is_body() || e.is_decode() => {
+            warn!("Skipping {} that couldn't be fetched: {}: {}", kind, url, e);
+            Ok(None)
+        },
+        e => Err(e)
+    }
 }
 
 fn extract_make_url(element: ElementRef) -> Result<String> {
@@ -106,27 +202,45 @@ fn extract_make_country(element: ElementRef) -> Result<String> {
     inner_html(element, "div.footer")
 }
 
+// Returns every make name in the grid, and the makes that pass the filter. A card that can't be
+// read is skipped, so one malformed make doesn't stop the crawl.
 #[time("info")]
-fn make_links(page: Page) -> Result<Vec<String>> {
+fn makes(page: Page, filter: &MakeFilter) -> (Vec<String>, Vec<Make>) {
     info!("Parsing for Make links...");
 
-    let mut links = vec![];
+    let mut discovered = vec![];
+    let mut makes = vec![];
 
     for div in page.elements("div.grid div[class^=\"m\"]") {
         debug!("HTML: {:?}", div.inner_html().trim());
 
-        let name = extract_make_name(div)?;
-
-        if MAKES.contains(name.as_str()) {
-            let url = extract_make_url(div)?;
-            let country = extract_make_country(div)?;
-            info!("Link found for Make: {} ({}) - {}", name, country, url);
+        let name = match extract_make_name(div) {
+            Ok(name) => name,
+            Err(e)   => {
+                warn!("Skipping Make card without a name: {}", e);
+                continue;
+            }
+        };
+        discovered.push(name.clone());
 
-            links.push(url);
+        if !filter.matches(&name) {
+            debug!("Skipping filtered Make: {}", name);
+            continue;
         }
+
+        let (url, country) = match (extract_make_url(div), extract_make_country(div)) {
+            (Ok(url), Ok(country)) => (url, country),
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("Skipping Make {}: {}", name, e);
+                continue;
+            }
+        };
+        info!("Link found for Make: {} ({}) - {}", name, country, url);
+
+        makes.push(Make { name, country, url });
     }
 
-    Ok(links)
+    (discovered, makes)
 }
 
 fn extract_model_url(element: ElementRef) -> Result<String> {
@@ -148,49 +262,268 @@ fn extract_model_year(element: ElementRef) -> Result<String> {
     Ok(elem.inner_html())
 }
 
-fn extract_model_market(element: ElementRef) -> Result<String> {
+fn extract_model_markets(element: ElementRef) -> Result<BTreeSet<String>> {
     let span = element_within(element, &["div.card-head"])?;
-    let elem = element_within(span, &["abbr.market"])?;
-    Ok(elem.inner_html())
+    Ok(elements_within(span, "abbr.market").into_iter().map(|elem| elem.inner_html()).collect())
 }
 
+fn extract_pagination_links(page: &Page) -> Vec<String> {
+    let base = match reqwest::Url::parse(&page.url) {
+        Ok(base) => base,
+        Err(e)   => {
+            warn!("Unable to resolve pagination links on {}: {}", page.url, e);
+            return vec![];
+        }
+    };
+
+    page.html.select(&PAGINATION_SELECTOR)
+        .filter_map(|a| a.value().attr("href"))
+        .filter_map(|href| match base.join(href) {
+            Ok(url) => Some(url.to_string()),
+            Err(e)  => {
+                warn!("Unable to resolve pagination link '{}' on {}: {}", href, page.url, e);
+                None
+            }
+        })
+        .collect()
+}
+
+// Identifies a make page however it was linked. The first page is linked both without a page number
+// and as "page=1", so the two are the same page.
+fn pagination_key(url: &str) -> String {
+    let normalized = normalize_url(url);
+    let mut parsed = match reqwest::Url::parse(&normalized) {
+        Ok(parsed) => parsed,
+        Err(_)     => return normalized
+    };
+
+    let pairs: Vec<(String, String)> = parsed.query_pairs()
+        .filter(|(key, value)| !(key == "page" && value == "1"))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+
+    if pairs.is_empty() {
+        parsed.set_query(None);
+    } else {
+        parsed.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+
+    parsed.to_string()
+}
+
+// Large makes split their model cards across several pages, so every pagination link is followed once.
 #[time("info")]
-fn model_links(page: Page) -> Result<Vec<String>> {
-    let make = extract_model_make(page.html.root_element())?;
+fn model_listings<F: Fn(&str) -> Result<Option<Page>>>(page: Page, options: &Options, fetch: F) -> Result<Vec<ModelListing>> {
+    let make = match extract_model_make(page.html.root_element()) {
+        Ok(make) => make,
+        Err(e)   => {
+            warn!("Skipping Make page without a Make name: {}: {}", page.url, e);
+            return Ok(vec![]);
+        }
+    };
     info!("Parsing for Model links for {}...", make);
 
-    let mut links = vec![];
+    let mut listings: Vec<ModelListing> = vec![];
+    let mut indices: BTreeMap<String, usize> = BTreeMap::new();
+    let mut visited: BTreeSet<String> = vec![pagination_key(&page.url)].into_iter().collect();
+    let mut pages: VecDeque<Page> = vec![page].into_iter().collect();
+
+    while let Some(page) = pages.pop_front() {
+        for url in extract_pagination_links(&page) {
+            if visited.insert(pagination_key(&url)) {
+                info!("Following pagination link for {}: {}", make, url);
+                pages.extend(fetch(&url).or_else(|e| skip_page("pagination page", &url, e))?);
+            }
+        }
+
+        model_cards(&page, &make, options, &mut listings, &mut indices);
+    }
 
+    Ok(listings)
+}
+
+fn model_cards(page: &Page, make: &str, options: &Options, listings: &mut Vec<ModelListing>, indices: &mut BTreeMap<String, usize>) {
     for div in page.elements("div.grid div.grid-card") {
         debug!("HTML: {:?}", div.inner_html().trim());
 
-        let market = match extract_model_market(div) {
-            Ok(market) => market,
-            Err(_)     => String::from("")
+        let listing_markets = extract_model_markets(div).unwrap_or_default();
+
+        if listing_markets.is_disjoint(&options.markets) {
+            continue;
+        }
+
+        let url = match extract_model_url(div) {
+            Ok(url) => normalize_url(&url),
+            Err(e)  => {
+                warn!("Skipping Model card without a link for {}: {}", make, e);
+                continue;
+            }
+        };
+        let name = match extract_model_name(div) {
+            Ok(name) => Some(name),
+            Err(_)   => {
+                warn!("Unable to find name for model: {}", url);
+                None
+            }
+        };
+        let year = match extract_model_year(div) {
+            Ok(year) => Some(year),
+            Err(_)   => {
+                warn!("Unable to find year for model: {}", url);
+                None
+            }
         };
 
-        if market == "US" {
-            let url = extract_model_url(div)?;
-            let name = match extract_model_name(div) {
-                Ok(name) => name,
-                Err(_)   => {
-                    warn!("Unable to find name for model: {}", url);
-                    "".to_string()
-                }
-            };
-            let year = match extract_model_year(div) {
-                Ok(year) => year,
-                Err(_)   => {
-                    warn!("Unable to find year for model: {}", url);
-                    "".to_string()
-                }
-            };
+        if !year_in_range(year.as_deref(), options) {
+            debug!("Skipping Model outside of the requested years: {} - {}", year.as_deref().unwrap_or_default(), url);
+            continue;
+        }
+
+        info!("Link found for Model ({:?} Markets): {} {} {} - {}",
+              listing_markets, year.as_deref().unwrap_or_default(), make, name.as_deref().unwrap_or_default(), url);
 
-            info!("Link found for Model ({} Market): {} {} {} - {}", market, year, make, name, url);
-            links.push(url);
+        // The same page can be listed once per market, so merge those into a single listing.
+        match indices.get(&url) {
+            Some(&index) => listings[index].markets.extend(listing_markets),
+            None         => {
+                indices.insert(url.clone(), listings.len());
+                listings.push(ModelListing { make: make.to_string(), name, year, markets: listing_markets, url, gone: false });
+            }
         }
     }
+}
+
+// Listings without a readable year are kept, since they can't be ruled out.
+fn year_in_range(year: Option<&str>, options: &Options) -> bool {
+    let year = match year.and_then(|year| year.get(..4)).and_then(|year| year.parse::<u16>().ok()) {
+        Some(year) => year,
+        None       => return true
+    };
 
-    Ok(links)
+    options.from_year.is_none_or(|from| year >= from) && options.to_year.is_none_or(|to| year <= to)
 }
 
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    const MAKE_URL: &str = "https://carfolio.com/specifications/models/?man=ford";
+    const FIRST_PAGE_URL: &str = "https://carfolio.com/specifications/models/?man=ford&page=1";
+    const SECOND_PAGE_URL: &str = "https://carfolio.com/specifications/models/?man=ford&page=2";
+
+    fn fixture_page(url: &str) -> Result<Option<Page>> {
+        match url {
+            FIRST_PAGE_URL  => Ok(Some(Page::from_html(url, include_str!("../../tests/fixtures/ford_page_1.html")))),
+            SECOND_PAGE_URL => Ok(Some(Page::from_html(url, include_str!("../../tests/fixtures/ford_page_2.html")))),
+            _               => panic!("Unexpected fetch of {}", url)
+        }
+    }
+
+    fn options() -> Options {
+        Options {
+            strict: false,
+            makes: MakeFilter::new(&[], &[]).unwrap(),
+            markets: vec![String::from("US")].into_iter().collect(),
+            from_year: None,
+            to_year: None,
+            catalog_only: false,
+            incremental: None
+        }
+    }
+
+    #[test]
+    fn makes_skips_unreadable_cards_and_filters_before_extracting() {
+        let page = Page::from_html("https://carfolio.com/specifications", r#"
+            <div class="grid">
+                <div class="m1"><a class="man" href="models/?man=ford">Ford</a><div class="footer">USA</div></div>
+                <div class="m2"><a class="man" href="models/?man=zil">ZIL</a></div>
+                <div class="m3"><a class="man">Mazda</a><div class="footer">Japan</div></div>
+                <div class="m4"><div class="footer">Nowhere</div></div>
+            </div>
+        "#);
+        let filter = MakeFilter::new(&[String::from("Ford"), String::from("Mazda")], &[]).unwrap();
+
+        let (discovered, makes) = makes(page, &filter);
+
+        assert_eq!(discovered, vec!["Ford", "ZIL", "Mazda"]);
+        assert_eq!(makes, vec![Make {
+            name: String::from("Ford"),
+            country: String::from("USA"),
+            url: String::from("https://carfolio.com/specifications/models/?man=ford")
+        }]);
+    }
+
+    #[test]
+    fn model_listings_skips_pagination_pages_that_fail() {
+        let options = options();
+        let missing = |url: &str| Err(Error::HttpError { status: StatusCode::NOT_FOUND, url: url.to_string() });
+
+        let listings = model_listings(fixture_page(FIRST_PAGE_URL).unwrap().unwrap(), &options, missing).unwrap();
+
+        assert_eq!(listings.len(), 1);
+    }
+
+    #[test]
+    fn model_listings_skips_model_cards_without_a_link() {
+        let options = options();
+        let page = Page::from_html("https://carfolio.com/specifications/models/?man=ford", r#"
+            <div class="manufacturer"><h2>Ford</h2></div>
+            <div class="grid">
+                <div class="grid-card"><div class="card-head"><span class="model name">Model T</span><abbr class="market">US</abbr></div></div>
+                <div class="grid-card"><div class="card-head"><a href="specifications/models/car/?car=1001">Mustang</a><abbr class="market">US</abbr></div></div>
+            </div>
+        "#);
+
+        let listings = model_listings(page, &options, fixture_page).unwrap();
+
+        assert_eq!(listings.len(), 1);
+        assert_eq!(listings[0].url, "https://carfolio.com/specifications/models/car/?car=1001");
+    }
+
+    #[test]
+    fn model_listings_skips_make_pages_without_a_make_name() {
+        let options = options();
+        let page = Page::from_html("https://carfolio.com/specifications/models/?man=ford", "<div class=\"grid\"></div>");
+
+        assert!(model_listings(page, &options, fixture_page).unwrap().is_empty());
+    }
+
+    #[test]
+    fn model_listings_does_not_fetch_the_first_page_again() {
+        let first_page = Page::from_html(MAKE_URL, include_str!("../../tests/fixtures/ford_page_1.html"));
+        let fetched = RefCell::new(vec![]);
+        let fetch = |url: &str| {
+            fetched.borrow_mut().push(url.to_string());
+            fixture_page(url)
+        };
+
+        let listings = model_listings(first_page, &options(), fetch).unwrap();
+
+        assert_eq!(fetched.into_inner(), vec![SECOND_PAGE_URL]);
+        assert_eq!(listings.len(), 2);
+    }
+
+    #[test]
+    fn pagination_key_treats_the_first_page_as_the_make_page() {
+        assert_eq!(pagination_key(FIRST_PAGE_URL), pagination_key(MAKE_URL));
+        assert_eq!(pagination_key("http://www.carfolio.com/specifications/models/?man=ford&page=1#top"), pagination_key(MAKE_URL));
+        assert_ne!(pagination_key(SECOND_PAGE_URL), pagination_key(MAKE_URL));
+    }
+
+    #[test]
+    fn model_listings_follows_pagination() {
+        let options = options();
+
+        let listings = model_listings(fixture_page(FIRST_PAGE_URL).unwrap().unwrap(), &options, fixture_page).unwrap();
+        let urls: Vec<&str> = listings.iter().map(|listing| listing.url.as_str()).collect();
+
+        assert_eq!(urls, vec![
+            "https://carfolio.com/specifications/models/car/?car=1001",
+            "https://carfolio.com/specifications/models/car/?car=1003"
+        ]);
+        assert_eq!(listings[1].name.as_deref(), Some("GT"));
+        assert!(listings.iter().all(|listing| listing.make == "Ford"));
+    }
+}
@@ -0,0 +1,239 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+
+use crate::error::{Error, Result};
+use crate::{http, warc, Page};
+
+use super::BASE_URL;
+
+struct Rule {
+    allow: bool,
+    path: String,
+    regex: Regex
+}
+
+#[derive(Default)]
+struct Group {
+    matched: bool,
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>
+}
+
+// The robots.txt rules that apply to this crawler, along with its Crawl-delay.
+pub(super) struct Robots {
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+    last_fetch: Cell<Option<Instant>>
+}
+
+impl Robots {
+    // A missing robots.txt (any 4xx) allows everything, but an unreachable one disallows everything (RFC 9309).
+    pub(super) fn fetch() -> Robots {
+        let url = format!("{}/robots.txt", BASE_URL);
+
+        match Page::get_html(&url) {
+            Ok((body, _)) => Self::parse(&body, http::user_agent()),
+            Err(Error::HttpError { status, .. }) if status.is_client_error() => {
+                warn!("No robots.txt at {} (HTTP {}), crawling without restrictions", url, status);
+                Self::parse("", http::user_agent())
+            },
+            Err(e) => {
+                warn!("Unable to fetch {}, not crawling anything: {}", url, e);
+                Self::disallow_all()
+            }
+        }
+    }
+
+    fn disallow_all() -> Robots {
+        let rule = Rule { allow: false, path: String::from("/"), regex: rule_regex("/") };
+        Robots { rules: vec![rule], crawl_delay: None, last_fetch: Cell::new(None) }
+    }
+
+    // Records naming this crawler's User-Agent take precedence over the ones for every agent ("*").
+    pub(super) fn parse(body: &str, user_agent: &str) -> Robots {
+        // Robots.txt matches on the product token, e.g. "carfolio-scraper" in "carfolio-scraper/0.1.0".
+        let product = user_agent.split('/').next().unwrap_or_default().trim().to_lowercase();
+
+        let mut specific = Group::default();
+        let mut wildcard = Group::default();
+        let mut in_group = false;
+        let mut applies = (false, false);
+
+        for line in body.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) => (key.trim().to_lowercase(), value.trim()),
+                None               => continue
+            };
+
+            match key.as_str() {
+                "user-agent" => {
+                    // Consecutive User-agent lines share the rules that follow them.
+                    if in_group {
+                        applies = (false, false);
+                        in_group = false;
+                    }
+
+                    let agent = value.to_lowercase();
+                    applies.0 |= !product.is_empty() && agent == product;
+                    applies.1 |= agent == "*";
+                    specific.matched |= applies.0;
+                },
+                "allow" | "disallow" => {
+                    in_group = true;
+
+                    if !value.is_empty() {
+                        let rule = || Rule { allow: key == "allow", path: value.to_string(), regex: rule_regex(value) };
+
+                        if applies.0 { specific.rules.push(rule()) }
+                        if applies.1 { wildcard.rules.push(rule()) }
+                    }
+                },
+                "crawl-delay" => {
+                    in_group = true;
+
+                    if applies.0 || applies.1 {
+                        // Values like "inf" or "1e400" parse, but are too large for a Duration.
+                        match value.parse::<f64>().ok().and_then(|seconds| Duration::try_from_secs_f64(seconds).ok()) {
+                            Some(delay) => {
+                                if applies.0 { specific.crawl_delay = Some(delay) }
+                                if applies.1 { wildcard.crawl_delay = Some(delay) }
+                            },
+                            None => warn!("Ignoring unparseable Crawl-delay in robots.txt: '{}'", value)
+                        }
+                    }
+                },
+                _ => ()
+            }
+        }
+
+        let group = if specific.matched { specific } else { wildcard };
+        info!("Loaded {} robots.txt rules for '{}' (Crawl-delay: {:?})", group.rules.len(), user_agent, group.crawl_delay);
+
+        Robots { rules: group.rules, crawl_delay: group.crawl_delay, last_fetch: Cell::new(None) }
+    }
+
+    // The longest matching rule wins, and Allow wins a tie.
+    fn disallowed_by(&self, url: &str) -> Option<&str> {
+        let path = match reqwest::Url::parse(url) {
+            Ok(url) => match url.query() {
+                Some(query) => format!("{}?{}", url.path(), query),
+                None        => url.path().to_string()
+            },
+            Err(_) => return None
+        };
+
+        self.rules.iter()
+            .filter(|rule| rule.regex.is_match(&path))
+            .max_by_key(|rule| (rule.path.len(), rule.allow))
+            .filter(|rule| !rule.allow)
+            .map(|rule| rule.path.as_str())
+    }
+
+    // Fetches a page unless robots.txt disallows it, waiting out the Crawl-delay since the previous fetch.
+    pub(super) fn fetch_page(&self, url: &str) -> Result<Option<Page>> {
+        if let Some(rule) = self.disallowed_by(url) {
+            warn!("Skipping {}: disallowed by robots.txt rule 'Disallow: {}'", url, rule);
+            return Ok(None);
+        }
+
+        // Replaying an archive doesn't touch the site, so there's nothing to wait for.
+        if let (Some(delay), Some(last_fetch), false) = (self.crawl_delay, self.last_fetch.get(), warc::is_replaying()) {
+            let elapsed = last_fetch.elapsed();
+
+            if elapsed < delay {
+                debug!("Waiting {:?} for the robots.txt Crawl-delay", delay - elapsed);
+                std::thread::sleep(delay - elapsed);
+            }
+        }
+
+        self.last_fetch.set(Some(Instant::now()));
+        Page::new(url).map(Some)
+    }
+}
+
+// Rules are path prefixes, where '*' matches any run of characters and a trailing '$' anchors the end.
+fn rule_regex(path: &str) -> Regex {
+    let (path, anchored) = match path.strip_suffix('$') {
+        Some(path) => (path, true),
+        None       => (path, false)
+    };
+    let pattern = path.split('*').map(regex::escape).collect::<Vec<_>>().join(".*");
+
+    Regex::new(&format!("^{}{}", pattern, if anchored { "$" } else { "" })).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AGENT: &str = "carfolio-scraper/0.1.0";
+
+    fn url(path: &str) -> String {
+        format!("{}{}", BASE_URL, path)
+    }
+
+    #[test]
+    fn parse_prefers_the_group_naming_this_crawler() {
+        let robots = Robots::parse("User-agent: *\nDisallow: /\n\nUser-agent: carfolio-scraper\nDisallow: /private/\nCrawl-delay: 2\n", AGENT);
+
+        assert_eq!(robots.disallowed_by(&url("/spec/")), None);
+        assert_eq!(robots.disallowed_by(&url("/private/page")), Some("/private/"));
+        assert_eq!(robots.crawl_delay, Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn parse_falls_back_to_the_wildcard_group() {
+        let robots = Robots::parse("User-agent: otherbot\nDisallow: /\n\nUser-agent: *\nDisallow: /search\n", AGENT);
+
+        assert_eq!(robots.disallowed_by(&url("/")), None);
+        assert_eq!(robots.disallowed_by(&url("/search?q=ford")), Some("/search"));
+    }
+
+    #[test]
+    fn parse_shares_rules_between_consecutive_user_agents() {
+        let robots = Robots::parse("User-agent: otherbot\nUser-agent: carfolio-scraper\nDisallow: /a\n\nUser-agent: otherbot\nDisallow: /b\n", AGENT);
+
+        assert_eq!(robots.disallowed_by(&url("/a")), Some("/a"));
+        assert_eq!(robots.disallowed_by(&url("/b")), None);
+    }
+
+    #[test]
+    fn parse_ignores_crawl_delays_too_large_for_a_duration() {
+        for delay in ["inf", "1e400", "-1", "soon"] {
+            let robots = Robots::parse(&format!("User-agent: *\nCrawl-delay: {}\n", delay), AGENT);
+            assert_eq!(robots.crawl_delay, None, "Crawl-delay: {}", delay);
+        }
+    }
+
+    #[test]
+    fn disallowed_by_lets_the_longest_match_win() {
+        let robots = Robots::parse("User-agent: *\nDisallow: /spec/\nAllow: /spec/models/\n", AGENT);
+
+        assert_eq!(robots.disallowed_by(&url("/spec/models/?man=ford")), None);
+        assert_eq!(robots.disallowed_by(&url("/spec/car/?car=1")), Some("/spec/"));
+    }
+
+    #[test]
+    fn disallowed_by_lets_allow_win_a_tie() {
+        let robots = Robots::parse("User-agent: *\nDisallow: /page\nAllow: /page\n", AGENT);
+
+        assert_eq!(robots.disallowed_by(&url("/page")), None);
+    }
+
+    #[test]
+    fn disallowed_by_supports_wildcards_and_end_anchors() {
+        let robots = Robots::parse("User-agent: *\nDisallow: /*.pdf$\nDisallow: /*?print=\n", AGENT);
+
+        assert_eq!(robots.disallowed_by(&url("/brochures/ford.pdf")), Some("/*.pdf$"));
+        assert_eq!(robots.disallowed_by(&url("/brochures/ford.pdf?page=2")), None);
+        assert_eq!(robots.disallowed_by(&url("/spec/car/?print=1")), Some("/*?print="));
+    }
+
+    #[test]
+    fn disallow_all_disallows_every_page() {
+        assert_eq!(Robots::disallow_all().disallowed_by(&url("/spec/")), Some("/"));
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::Specification;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub(crate) enum BrakeType {
+    CarbonCeramic,
+    VentilatedDisc,
+    Disc,
+    Drum
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct Brake {
+    brake_type: Option<BrakeType>,
+    diameter: Specification<u16>
+}
+
+pub(super) fn set_brake_types(brakes: &mut BTreeMap<String, Option<Brake>>, types: Option<(Option<BrakeType>, Option<BrakeType>)>) -> bool {
+    let (front, rear) = types.unwrap_or((None, None));
+
+    for (axle, brake_type) in [("Front", front), ("Rear", rear)] {
+        let brake = brakes.entry(axle.to_string()).or_insert(None);
+
+        if let Some(brake_type) = brake_type {
+            brake.get_or_insert_with(Brake::default).brake_type = Some(brake_type);
+        }
+    }
+
+    types.is_some()
+}
+
+pub(super) fn set_brake_diameter(brakes: &mut BTreeMap<String, Option<Brake>>, axle: &str, diameter: Specification<u16>) -> bool {
+    let brake = brakes.entry(axle.to_string()).or_insert(None);
+
+    match diameter {
+        Some(diameter) => {
+            brake.get_or_insert_with(Brake::default).diameter = Some(diameter);
+            true
+        },
+        None => false
+    }
+}
+
+// Carfolio lists brakes as front/rear pairs, e.g. "VenDisc/Disc" or "Disc/Drum". A single type applies to both axles.
+pub(super) fn extract_brake_types(string: String) -> Option<(Option<BrakeType>, Option<BrakeType>)> {
+    let mut splits = string.split('/');
+    let front = extract_brake_type(splits.next()?);
+    let rear = match splits.next() {
+        Some(rear) => {
+            let brake_type = extract_brake_type(rear);
+            if brake_type.is_none() && front.is_some() {
+                warn!("Could not parse rear brake type from '{}'", string);
+            }
+            brake_type
+        },
+        None => front
+    };
+
+    if front.is_none() && rear.is_none() {
+        warn!("Could not parse brake types from '{}'", string);
+        return None;
+    }
+
+    Some((front, rear))
+}
+
+fn extract_brake_type(string: &str) -> Option<BrakeType> {
+    let lower = string.to_lowercase();
+
+    if lower.contains("ceramic") || lower.contains("carbon") || lower.contains("ccb") {
+        Some(BrakeType::CarbonCeramic)
+    } else if lower.contains("ven") {
+        Some(BrakeType::VentilatedDisc)
+    } else if lower.contains("disc") {
+        Some(BrakeType::Disc)
+    } else if lower.contains("drum") {
+        Some(BrakeType::Drum)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn types(string: &str) -> Option<(Option<BrakeType>, Option<BrakeType>)> {
+        extract_brake_types(String::from(string))
+    }
+
+    #[test]
+    fn extract_brake_types_reads_front_and_rear() {
+        assert_eq!(types("VenDisc/Drum"), Some((Some(BrakeType::VentilatedDisc), Some(BrakeType::Drum))));
+        assert_eq!(types("Carbon ceramic/Disc"), Some((Some(BrakeType::CarbonCeramic), Some(BrakeType::Disc))));
+    }
+
+    #[test]
+    fn extract_brake_types_applies_a_single_type_to_both_axles() {
+        assert_eq!(types("Disc"), Some((Some(BrakeType::Disc), Some(BrakeType::Disc))));
+    }
+
+    #[test]
+    fn extract_brake_types_leaves_an_unrecognised_rear_empty() {
+        assert_eq!(types("VenDisc/Band"), Some((Some(BrakeType::VentilatedDisc), None)));
+        assert_eq!(types("VenDisc/"), Some((Some(BrakeType::VentilatedDisc), None)));
+    }
+
+    #[test]
+    fn extract_brake_types_rejects_unrecognised_types() {
+        assert_eq!(types("Band/Band"), None);
+        assert_eq!(types("Band"), None);
+    }
+
+    #[test]
+    fn set_brake_types_keeps_both_axles() {
+        let mut brakes = BTreeMap::new();
+
+        assert!(set_brake_types(&mut brakes, types("Disc/Band")));
+        assert_eq!(brakes["Front"].as_ref().and_then(|brake| brake.brake_type), Some(BrakeType::Disc));
+        assert_eq!(brakes["Rear"], None);
+    }
+}
@@ -0,0 +1,198 @@
+use regex::Regex;
+use serde::Serialize;
+
+use super::{Origin, Specification, Vehicle};
+
+static AGREEMENT_TOLERANCE: f32 = 0.05;
+
+// A metric computed locally from base fields, alongside carfolio's own figure when it has one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct DerivedMetric {
+    value: f32,
+    unit: &'static str,
+    origin: Origin,
+    carfolio_value: Option<f32>,
+    agrees_with_carfolio: Option<bool>
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct DerivedMetrics {
+    specific_output: Option<DerivedMetric>,
+    specific_torque: Option<DerivedMetric>,
+    power_to_weight: Option<DerivedMetric>,
+    bore_stroke_ratio: Option<DerivedMetric>,
+    length_wheelbase_ratio: Option<DerivedMetric>,
+    cda: Option<DerivedMetric>
+}
+
+impl DerivedMetric {
+    fn new(name: &str, value: f32, unit: &'static str, carfolio_value: Option<f32>) -> DerivedMetric {
+        let agrees_with_carfolio = carfolio_value.map(|carfolio_value| {
+            (value - carfolio_value).abs() <= carfolio_value.abs() * AGREEMENT_TOLERANCE
+        });
+
+        if agrees_with_carfolio == Some(false) {
+            warn!("Derived {} of {:.2} {} disagrees with carfolio's {:?}", name, value, unit, carfolio_value);
+        }
+
+        DerivedMetric { value, unit, origin: Origin::Derived, carfolio_value, agrees_with_carfolio }
+    }
+}
+
+pub(super) fn derive_metrics(vehicle: &Vehicle) -> DerivedMetrics {
+    let litres = in_unit(&vehicle.displacement, "litre");
+    let power = vehicle.power.get("Value").and_then(|power| in_unit(power, "kW"));
+    let torque = vehicle.torque.get("Value").and_then(|torque| in_unit(torque, "Nm"));
+    let weight = in_unit(&vehicle.curb_weight, "kg");
+
+    let length_wheelbase = match (&vehicle.length, &vehicle.wheelbase) {
+        (Some((length, length_unit)), Some((wheelbase, wheelbase_unit))) if length_unit == wheelbase_unit && *wheelbase > 0 => {
+            Some(*length as f32 / *wheelbase as f32)
+        },
+        _ => None
+    };
+
+    DerivedMetrics {
+        specific_output: ratio(power, litres).map(|value| {
+            DerivedMetric::new("specific_output", value, "kW/litre", in_unit(&vehicle.specific_output, "kW/litre"))
+        }),
+        specific_torque: ratio(torque, litres).map(|value| {
+            DerivedMetric::new("specific_torque", value, "Nm/litre", in_unit(&vehicle.specific_torque, "Nm/litre"))
+        }),
+        power_to_weight: ratio(power, weight.map(|weight| weight / 1000.0)).map(|value| {
+            DerivedMetric::new("power_to_weight", value, "kW/tonne", in_unit(&vehicle.power_to_weight_ratio, "kW/tonne"))
+        }),
+        bore_stroke_ratio: vehicle.bore_stroke.as_ref().and_then(|(bore_stroke, _)| bore_stroke.ratio()).map(|value| {
+            DerivedMetric::new("bore_stroke_ratio", value, "", vehicle.bore_stroke_ratio)
+        }),
+        length_wheelbase_ratio: length_wheelbase.map(|value| {
+            DerivedMetric::new("length_wheelbase_ratio", value, "", vehicle.length_wheelbase_ratio)
+        }),
+        cda: vehicle.drag_coefficient.zip(in_unit(&vehicle.frontal_area, "m²")).map(|(cd, area)| {
+            DerivedMetric::new("cda", cd * area, "m²", in_unit(&vehicle.cda, "m²"))
+        })
+    }
+}
+
+// Carfolio lists specific output as e.g. "125.9 bhp/litre, 92.6 kW/litre".
+pub(super) fn extract_specific_output(string: String) -> Specification<f32> {
+    extract_f32_in_unit(string, "kW/litre")
+}
+
+// Carfolio lists specific torque as e.g. "131.5 Nm/litre".
+pub(super) fn extract_specific_torque(string: String) -> Specification<f32> {
+    extract_f32_in_unit(string, "Nm/litre")
+}
+
+fn extract_f32_in_unit(string: String, unit: &str) -> Specification<f32> {
+    let re = Regex::new(&format!(r"(\d+(?:\.\d+)?)\s*{}", regex::escape(unit))).unwrap();
+
+    match re.captures(&string) {
+        Some(caps) => {
+            let amount = caps.get(1)?.as_str().parse::<f32>().ok()?;
+            Some((amount, unit.to_string()))
+        },
+        None => {
+            warn!("Unable to find matches in '{}' with regex '{}'", string, re);
+            None
+        }
+    }
+}
+
+fn in_unit<T: Copy + Into<f32>>(specification: &Specification<T>, unit: &str) -> Option<f32> {
+    match specification {
+        Some((value, value_unit)) if value_unit == unit => Some((*value).into()),
+        _                                               => None
+    }
+}
+
+fn ratio(numerator: Option<f32>, denominator: Option<f32>) -> Option<f32> {
+    match (numerator, denominator) {
+        (Some(numerator), Some(denominator)) if denominator > 0.0 => Some(numerator / denominator),
+        _                                                          => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::engine::extract_bore_stroke;
+
+    fn spec<T>(value: T, unit: &str) -> Specification<T> {
+        Some((value, unit.to_string()))
+    }
+
+    fn engine(power: u16, litres: f32) -> Vehicle {
+        let mut vehicle = Vehicle { displacement: spec(litres, "litre"), ..Vehicle::default() };
+        vehicle.power.insert(String::from("Value"), spec(power, "kW"));
+        vehicle
+    }
+
+    #[test]
+    fn derive_metrics_computes_specific_output() {
+        let metric = derive_metrics(&engine(150, 2.0)).specific_output.unwrap();
+
+        assert_eq!((metric.value, metric.unit, metric.origin), (75.0, "kW/litre", Origin::Derived));
+        assert_eq!((metric.carfolio_value, metric.agrees_with_carfolio), (None, None));
+    }
+
+    #[test]
+    fn derive_metrics_agrees_with_carfolio_within_the_tolerance() {
+        let agrees = |carfolio_value: f32| {
+            let vehicle = Vehicle { specific_output: spec(carfolio_value, "kW/litre"), ..engine(150, 2.0) };
+            derive_metrics(&vehicle).specific_output.unwrap().agrees_with_carfolio
+        };
+
+        assert_eq!(agrees(77.0), Some(true));
+        assert_eq!(agrees(72.0), Some(true));
+        assert_eq!(agrees(80.0), Some(false));
+        assert_eq!(agrees(70.0), Some(false));
+    }
+
+    #[test]
+    fn derive_metrics_skips_fields_in_other_units() {
+        let mut vehicle = engine(150, 2.0);
+        vehicle.power.insert(String::from("Value"), spec(201, "bhp"));
+
+        assert_eq!(derive_metrics(&vehicle).specific_output, None);
+    }
+
+    #[test]
+    fn derive_metrics_skips_zero_denominators() {
+        let mut vehicle = Vehicle { curb_weight: spec(0, "kg"), length: spec(4500, "mm"), wheelbase: spec(0, "mm"), ..engine(150, 0.0) };
+        vehicle.torque.insert(String::from("Value"), spec(300, "Nm"));
+        vehicle.bore_stroke = extract_bore_stroke(String::from("86 x 0 mm"));
+
+        assert_eq!(derive_metrics(&vehicle), DerivedMetrics::default());
+    }
+
+    #[test]
+    fn derive_metrics_computes_ratios() {
+        let vehicle = Vehicle {
+            curb_weight: spec(1500, "kg"),
+            length: spec(4500, "mm"),
+            wheelbase: spec(2500, "mm"),
+            bore_stroke: extract_bore_stroke(String::from("86 x 86 mm")),
+            drag_coefficient: Some(0.3),
+            frontal_area: spec(2.0, "m²"),
+            ..engine(150, 2.0)
+        };
+        let metrics = derive_metrics(&vehicle);
+
+        assert_eq!(metrics.power_to_weight.map(|metric| metric.value), Some(100.0));
+        assert_eq!(metrics.length_wheelbase_ratio.map(|metric| metric.value), Some(1.8));
+        assert_eq!(metrics.bore_stroke_ratio.map(|metric| metric.value), Some(1.0));
+        assert_eq!(metrics.cda.map(|metric| metric.value), Some(0.6));
+    }
+
+    #[test]
+    fn extract_specific_output_reads_the_kw_figure() {
+        assert_eq!(extract_specific_output(String::from("125.9 bhp/litre, 92.6 kW/litre")), spec(92.6, "kW/litre"));
+        assert_eq!(extract_specific_output(String::from("125.9 bhp/litre")), None);
+    }
+
+    #[test]
+    fn extract_specific_torque_reads_the_nm_figure() {
+        assert_eq!(extract_specific_torque(String::from("131.5 Nm/litre")), spec(131.5, "Nm/litre"));
+    }
+}
@@ -0,0 +1,124 @@
+use regex::Regex;
+use serde::Serialize;
+
+use super::{Specification, extract_string_with_unit};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub(crate) enum CylinderLayout {
+    Inline,
+    V,
+    Flat,
+    W,
+    Rotary { rotors: Option<u8> },
+    None
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct EngineConfig {
+    layout: CylinderLayout,
+    cylinder_count: u8
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct BoreStroke {
+    bore: f32,
+    stroke: f32
+}
+
+impl BoreStroke {
+    pub(super) fn ratio(&self) -> Option<f32> {
+        if self.stroke > 0.0 {
+            Some(self.bore / self.stroke)
+        } else {
+            None
+        }
+    }
+}
+
+// Carfolio lists cylinders as e.g. "V 8", "flat 6", "in-line 4" or "W 16".
+// Rotary engines and electric motors have no cylinders, so they get a count of zero.
+pub(super) fn extract_engine_config(string: String) -> Option<EngineConfig> {
+    let lower = string.to_lowercase();
+    let count = Regex::new(r"(\d+)").unwrap()
+        .captures(&lower)
+        .and_then(|caps| caps.get(1)?.as_str().parse::<u8>().ok());
+
+    if lower.contains("rotary") || lower.contains("wankel") || lower.contains("rotor") {
+        return Some(EngineConfig { layout: CylinderLayout::Rotary { rotors: count }, cylinder_count: 0 });
+    }
+
+    if lower.contains("electric") || lower.contains("none") || count == Some(0) {
+        return Some(EngineConfig { layout: CylinderLayout::None, cylinder_count: 0 });
+    }
+
+    let re = Regex::new(r"(?:^|[^a-z])(in-?line|straight|flat|boxer|opposed|vr|v|w|l|i|h)(?:[^a-z]|$)").unwrap();
+    let layout = match re.captures(&lower).and_then(|caps| caps.get(1)) {
+        Some(keyword) => match keyword.as_str() {
+            "v" | "vr"                         => CylinderLayout::V,
+            "w"                                => CylinderLayout::W,
+            "flat" | "boxer" | "opposed" | "h" => CylinderLayout::Flat,
+            _                                  => CylinderLayout::Inline
+        },
+        None => {
+            warn!("Could not parse cylinder layout from '{}' with regex '{}'", string, re);
+            return None;
+        }
+    };
+
+    match count {
+        Some(cylinder_count) => Some(EngineConfig { layout, cylinder_count }),
+        None                 => {
+            warn!("Could not parse cylinder count from '{}'", string);
+            None
+        }
+    }
+}
+
+// Carfolio lists bore and stroke as e.g. "86 x 86 mm".
+pub(super) fn extract_bore_stroke(string: String) -> Specification<BoreStroke> {
+    let (bore_stroke, unit) = extract_string_with_unit(string.replace(" x ", "x"))?;
+    let splits: Vec<&str> = bore_stroke.split('x').collect();
+    let bore = splits.first()?.parse::<f32>().ok()?;
+    let stroke = splits.get(1)?.parse::<f32>().ok()?;
+
+    Some((BoreStroke { bore, stroke }, unit))
+}
+
+// Carfolio lists ratios as e.g. "10.5:1" or "1.00".
+pub(super) fn extract_ratio(string: String) -> Option<f32> {
+    let ratio = string.split(':').next()?.trim();
+    ratio.parse::<f32>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(string: &str) -> Option<(CylinderLayout, u8)> {
+        extract_engine_config(String::from(string)).map(|config| (config.layout, config.cylinder_count))
+    }
+
+    #[test]
+    fn extract_engine_config_reads_layout_and_count() {
+        assert_eq!(config("V 8"), Some((CylinderLayout::V, 8)));
+        assert_eq!(config("flat 6"), Some((CylinderLayout::Flat, 6)));
+        assert_eq!(config("in-line 4"), Some((CylinderLayout::Inline, 4)));
+        assert_eq!(config("W 16"), Some((CylinderLayout::W, 16)));
+    }
+
+    #[test]
+    fn extract_engine_config_gives_rotary_engines_no_cylinders() {
+        assert_eq!(config("2 rotor Wankel rotary"), Some((CylinderLayout::Rotary { rotors: Some(2) }, 0)));
+        assert_eq!(config("rotary"), Some((CylinderLayout::Rotary { rotors: None }, 0)));
+    }
+
+    #[test]
+    fn extract_engine_config_gives_electric_motors_no_cylinders() {
+        assert_eq!(config("electric"), Some((CylinderLayout::None, 0)));
+    }
+
+    #[test]
+    fn extract_engine_config_rejects_a_layout_without_a_count() {
+        assert_eq!(config("V"), None);
+    }
+}
@@ -0,0 +1,198 @@
+use serde::Serialize;
+
+use super::{Origin, split_string};
+
+static LITRES_PER_100KM_US_MPG: f32 = 235.215;
+static LITRES_PER_100KM_UK_MPG: f32 = 282.481;
+static LITRES_PER_100KM_KM_PER_LITRE: f32 = 100.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct FuelFigure {
+    value: f32,
+    origin: Origin
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct FuelFigures {
+    city: Option<FuelFigure>,
+    highway: Option<FuelFigure>,
+    combined: Option<FuelFigure>
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct FuelEconomy {
+    us_mpg: FuelFigures,
+    uk_mpg: FuelFigures,
+    litres_per_100km: FuelFigures,
+    km_per_litre: FuelFigures
+}
+
+#[derive(Clone, Copy)]
+enum Cycle {
+    City,
+    Highway,
+    Combined
+}
+
+#[derive(Clone, Copy)]
+pub(super) enum Standard {
+    UsMpg,
+    UkMpg,
+    LitresPer100Km,
+    KmPerLitre
+}
+
+impl Standard {
+    fn convert(self, value: f32, to: Standard) -> f32 {
+        to.reciprocal(self.reciprocal(value))
+    }
+
+    // Every other standard is a constant over litres/100km, so the same mapping converts to and from it.
+    fn reciprocal(self, value: f32) -> f32 {
+        match self {
+            Standard::UsMpg          => LITRES_PER_100KM_US_MPG / value,
+            Standard::UkMpg          => LITRES_PER_100KM_UK_MPG / value,
+            Standard::LitresPer100Km => value,
+            Standard::KmPerLitre     => LITRES_PER_100KM_KM_PER_LITRE / value
+        }
+    }
+}
+
+impl FuelFigures {
+    fn get(&self, cycle: Cycle) -> Option<&FuelFigure> {
+        match cycle {
+            Cycle::City     => self.city.as_ref(),
+            Cycle::Highway  => self.highway.as_ref(),
+            Cycle::Combined => self.combined.as_ref()
+        }
+    }
+
+    fn set(&mut self, cycle: Cycle, figure: FuelFigure) {
+        match cycle {
+            Cycle::City     => self.city = Some(figure),
+            Cycle::Highway  => self.highway = Some(figure),
+            Cycle::Combined => self.combined = Some(figure)
+        }
+    }
+}
+
+impl FuelEconomy {
+    // Fills in every figure that wasn't scraped by converting from one that was,
+    // preferring US MPG, then litres/100km, UK MPG and km/litre.
+    pub(super) fn derive_missing(&mut self) {
+        let standards = [Standard::UsMpg, Standard::LitresPer100Km, Standard::UkMpg, Standard::KmPerLitre];
+
+        for cycle in [Cycle::City, Cycle::Highway, Cycle::Combined] {
+            let source = standards.iter().find_map(|&standard| {
+                let figure = self.figures(standard).get(cycle)?;
+                Some((standard, figure.value))
+            });
+
+            if let Some((from, value)) = source {
+                for &to in &standards {
+                    if self.figures(to).get(cycle).is_none() {
+                        let value = from.convert(value, to);
+                        self.figures_mut(to).set(cycle, FuelFigure { value, origin: Origin::Derived });
+                    }
+                }
+            }
+        }
+    }
+
+    fn figures(&self, standard: Standard) -> &FuelFigures {
+        match standard {
+            Standard::UsMpg          => &self.us_mpg,
+            Standard::UkMpg          => &self.uk_mpg,
+            Standard::LitresPer100Km => &self.litres_per_100km,
+            Standard::KmPerLitre     => &self.km_per_litre
+        }
+    }
+
+    fn figures_mut(&mut self, standard: Standard) -> &mut FuelFigures {
+        match standard {
+            Standard::UsMpg          => &mut self.us_mpg,
+            Standard::UkMpg          => &mut self.uk_mpg,
+            Standard::LitresPer100Km => &mut self.litres_per_100km,
+            Standard::KmPerLitre     => &mut self.km_per_litre
+        }
+    }
+}
+
+pub(super) fn set_fuel_figures(economy: &mut Option<FuelEconomy>, standard: Standard, figures: Option<FuelFigures>) -> bool {
+    match figures {
+        Some(figures) => {
+            debug!("{:?}", figures);
+            *economy.get_or_insert_with(FuelEconomy::default).figures_mut(standard) = figures;
+            true
+        },
+        None => false
+    }
+}
+
+// Carfolio lists fuel economy as "city/highway/combined", e.g. "17/25/20 mpg".
+// Two values are treated as city/highway and a single value as combined.
+pub(super) fn extract_fuel_figures(string: String) -> Option<FuelFigures> {
+    let split_string = split_string(string);
+    let figures_string = split_string.first()?;
+    let values = figures_string.split('/')
+        .map(|s| s.parse::<f32>().ok().map(|value| FuelFigure { value, origin: Origin::Scraped }))
+        .collect::<Option<Vec<FuelFigure>>>()?;
+
+    match values.as_slice() {
+        [combined] => Some(FuelFigures { combined: Some(combined.clone()), ..Default::default() }),
+        [city, highway] => Some(FuelFigures { city: Some(city.clone()), highway: Some(highway.clone()), combined: None }),
+        [city, highway, combined, ..] => Some(FuelFigures {
+            city: Some(city.clone()),
+            highway: Some(highway.clone()),
+            combined: Some(combined.clone())
+        }),
+        [] => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(figures: &FuelFigures) -> (Option<f32>, Option<f32>, Option<f32>) {
+        let value = |figure: &Option<FuelFigure>| figure.as_ref().map(|figure| figure.value);
+        (value(&figures.city), value(&figures.highway), value(&figures.combined))
+    }
+
+    fn figures(string: &str) -> FuelFigures {
+        extract_fuel_figures(String::from(string)).unwrap()
+    }
+
+    #[test]
+    fn extract_fuel_figures_reads_city_highway_and_combined() {
+        assert_eq!(values(&figures("17/25/20 mpg")), (Some(17.0), Some(25.0), Some(20.0)));
+    }
+
+    #[test]
+    fn extract_fuel_figures_reads_two_values_as_city_and_highway() {
+        assert_eq!(values(&figures("17/25 mpg")), (Some(17.0), Some(25.0), None));
+    }
+
+    #[test]
+    fn extract_fuel_figures_reads_one_value_as_combined() {
+        assert_eq!(values(&figures("8.1 l/100km")), (None, None, Some(8.1)));
+    }
+
+    #[test]
+    fn extract_fuel_figures_rejects_non_numeric_values() {
+        assert_eq!(extract_fuel_figures(String::from("n/a")), None);
+    }
+
+    #[test]
+    fn derive_missing_converts_between_standards() {
+        let mut economy = FuelEconomy { us_mpg: figures("20"), ..Default::default() };
+        economy.derive_missing();
+
+        let litres = economy.litres_per_100km.combined.unwrap();
+        assert!((litres.value - 11.76).abs() < 0.01);
+        assert_eq!(litres.origin, Origin::Derived);
+        assert!((economy.km_per_litre.combined.unwrap().value - 8.50).abs() < 0.01);
+        assert!((economy.uk_mpg.combined.unwrap().value - 24.02).abs() < 0.01);
+        assert_eq!(economy.us_mpg.combined.unwrap().origin, Origin::Scraped);
+    }
+}
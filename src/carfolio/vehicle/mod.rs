@@ -0,0 +1,359 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use regex::Regex;
+use scraper::element_ref::ElementRef;
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::{element_within, inner_text};
+use crate::Page;
+
+mod brakes;
+mod derived;
+mod engine;
+mod fuel_economy;
+mod powertrain;
+mod provenance;
+mod registry;
+mod validation;
+mod weight;
+mod wheels;
+
+use brakes::Brake;
+use derived::DerivedMetrics;
+use engine::{BoreStroke, EngineConfig};
+use fuel_economy::FuelEconomy;
+use powertrain::Powertrain;
+use provenance::Provenance;
+use validation::Violation;
+use weight::WeightDistribution;
+use wheels::{TyreSpec, WheelSize};
+
+pub(crate) use provenance::Outcome;
+pub(crate) use registry::supported_fields;
+pub(crate) use validation::Severity;
+
+lazy_static! {
+    static ref IGNORED_ROWS: std::collections::BTreeSet<&'static str> = [
+        "universal fuel consumption (calculated from the above)"
+    ].iter().cloned().collect();
+
+    static ref USELESS_VALUES: std::collections::BTreeSet<&'static str> = [
+        "",
+        "N/A"
+    ].iter().cloned().collect();
+}
+
+type Specification<T> = Option<(T, String)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub(crate) enum Origin {
+    Scraped,
+    Derived
+}
+
+#[derive(Default, Serialize)]
+pub(crate) struct Vehicle {
+    aspiration: Option<String>,
+    body_type: Option<String>,
+    bore_stroke: Specification<BoreStroke>,
+    bore_stroke_ratio: Option<f32>,
+    brakes: BTreeMap<String, Option<Brake>>,
+    carfolio_id: Option<String>,
+    cda: Specification<f32>,
+    compression_ratio: Option<f32>,
+    curb_weight: Specification<u16>,
+    derived: DerivedMetrics,
+    displacement: Specification<f32>,
+    door_count: Option<u8>,
+    drag_coefficient: Option<f32>,
+    drive_wheel_config: Option<String>,
+    engine_code: Option<String>,
+    engine_config: Option<EngineConfig>,
+    engine_construction: Option<String>,
+    engine_coolant: Option<String>,
+    engine_layout: Option<String>,
+    engine_manufacturer: Option<String>,
+    engine_position: Option<String>,
+    engine_type: Option<String>,
+    extra_specs: BTreeMap<String, String>,
+    final_drive_ratio: Option<f32>,
+    frontal_area: Specification<f32>,
+    fuel_economy: Option<FuelEconomy>,
+    fuel_capacity: Specification<f32>,
+    ground_clearance: Specification<u16>,
+    height: Specification<u16>,
+    length: Specification<u16>,
+    length_wheelbase_ratio: Option<f32>,
+    make: String,
+    markets: BTreeSet<String>,
+    max_speed: Specification<u16>,
+    model: String,
+    power: BTreeMap<String, Specification<u16>>,
+    power_to_weight_ratio: Specification<f32>,
+    powertrain: Powertrain,
+    provenance: BTreeMap<String, Provenance>,
+    specific_output: Specification<f32>,
+    specific_torque: Specification<f32>,
+    steering_config: Option<String>,
+    tires: BTreeMap<String, Option<TyreSpec>>,
+    top_gear_ratio: Option<f32>,
+    torque: BTreeMap<String, Specification<u16>>,
+    track: BTreeMap<String, Specification<u16>>,
+    transmission: Option<String>,
+    url: String,
+    valve_config: Option<String>,
+    violations: Vec<Violation>,
+    weight_distribution: Option<WeightDistribution>,
+    weight_to_power_ratio: Specification<f32>,
+    wheel_size: BTreeMap<String, Option<WheelSize>>,
+    wheelbase: Specification<u16>,
+    width: Specification<u16>,
+    year: String,
+    zero_to_sixty: Specification<f32>
+}
+
+impl Vehicle {
+    pub(super) fn new(page: Page, markets: BTreeSet<String>) -> Result<Vehicle> {
+        let overview = element_within(page.html.root_element(), &["div h3 span.automobile"])?;
+        let make = extract_model_make(overview)?;
+        let model = extract_model_name(overview)?;
+        let year = extract_model_year(overview)?;
+        info!("Parsing Model specifications for {} {} {}", year, make, model);
+
+        let url = page.url.clone();
+
+        let mut specifications = extract_model_specifications_table(page)?;
+        debug!("Specifications for {} {} {}:\n{:#?}", year, make, model, specifications);
+
+        let mut vehicle = registry::parse_specifications(&mut specifications);
+
+        let unused_keys = registry::unused_keys(&specifications);
+
+        if !unused_keys.is_empty() {
+            warn!("Unused fields from Details map: {:#?}", unused_keys);
+        }
+
+        // Whatever wasn't parsed is kept verbatim, so new carfolio fields aren't lost before they get a parser.
+        vehicle.extra_specs = specifications;
+        vehicle.make = make;
+        vehicle.markets = markets;
+        vehicle.url = url;
+        vehicle.model = model;
+        vehicle.year = year;
+
+        vehicle.derived = derived::derive_metrics(&vehicle);
+        vehicle.violations = validation::validate(&vehicle);
+
+        for violation in &vehicle.violations {
+            warn!("Validation failed for {} {} {}: {:?}", vehicle.year, vehicle.make, vehicle.model, violation);
+        }
+
+        Ok(vehicle)
+    }
+
+    pub(crate) fn carfolio_id(&self) -> Option<&str> {
+        self.carfolio_id.as_deref()
+    }
+
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub(crate) fn make(&self) -> &str {
+        &self.make
+    }
+
+    pub(crate) fn year(&self) -> &str {
+        &self.year
+    }
+
+    pub(crate) fn has_violations(&self, severity: Severity) -> bool {
+        self.violations.iter().any(|violation| violation.severity() == severity)
+    }
+
+    pub(crate) fn provenance(&self) -> &BTreeMap<String, Provenance> {
+        &self.provenance
+    }
+
+    pub(crate) fn unused_keys(&self) -> Vec<&String> {
+        registry::unused_keys(&self.extra_specs).into_iter().map(|(key, _)| key).collect()
+    }
+}
+
+fn split_string(string: String) -> Vec<String> {
+    let re = Regex::new(r"[, ]+").unwrap();
+    re.split(&string).map(|s| s.to_string()).collect()
+}
+
+fn extract_max_speed(string: String) -> Specification<u16> {
+    let re = Regex::new(r"(\d+ mph)").unwrap();
+
+    match re.captures(&string) {
+        Some(caps) => {
+            match caps.get(1) {
+                Some(str) => extract_u16_with_unit(str.as_str().to_string()),
+                None      => {
+                    warn!("Maximum speed was unable to be parsed from '{}' with regex '{}'", string, re);
+                    None
+                }
+            }
+        },
+        None => {
+            warn!("Unable to find matches in '{}' with regex '{}'", string, re);
+            None
+        }
+    }
+}
+
+fn extract_power_to_weight_ratio(string: String) -> Specification<f32> {
+    let split: Vec<&str> = string.split(",").collect();
+    let string = split.get(1)?;
+
+    extract_f32_with_unit(String::from(string.trim()))
+}
+
+fn extract_torque(string: String) -> Option<BTreeMap<String, Specification<u16>>> {
+    extract_power_or_torque(string, Regex::new(r"(\d+ Nm).*\s(\d+ rpm)").unwrap())
+}
+
+fn extract_power(string: String) -> Option<BTreeMap<String, Specification<u16>>> {
+    extract_power_or_torque(string, Regex::new(r"(\d+ kW).*\s(\d+ rpm)").unwrap())
+}
+
+// Returns None unless the value itself parses, so an unrecognised row is reported as unparseable.
+fn extract_power_or_torque(string: String, re: Regex) -> Option<BTreeMap<String, Specification<u16>>> {
+    let caps = match re.captures(&string) {
+        Some(caps) => caps,
+        None       => {
+            warn!("Unable to find matches in '{}' with regex '{}'", string, re);
+            return None;
+        }
+    };
+
+    let value = match caps.get(1).and_then(|str| extract_u16_with_unit(str.as_str().to_string())) {
+        Some(value) => value,
+        None        => {
+            warn!("Value was unable to be parsed from '{}' with regex '{}'", string, re);
+            return None;
+        }
+    };
+    let rpm = match caps.get(2) {
+        Some(str) => extract_u16_with_unit(str.as_str().to_string()),
+        None      => {
+            warn!("RPM was unable to be parsed from '{}' with regex '{}'", string, re);
+            None
+        }
+    };
+
+    let mut map = BTreeMap::new();
+    map.insert(String::from("Value"), Some(value));
+    map.insert(String::from("RPM"), rpm);
+    Some(map)
+}
+
+fn extract_displacement(string: String) -> Specification<f32> {
+    let re = Regex::new(r"(\d+.\d+ litre)").unwrap();
+    match re.captures(&string) {
+        Some(caps) => {
+            match caps.get(1) {
+                Some(str) => extract_f32_with_unit(str.as_str().to_string()),
+                None      => {
+                    warn!("Could not parse displacement from '{}' with regex '{}'", string, re);
+                    None
+                }
+            }
+        },
+        None => {
+            warn!("Could not parse displacement from '{}' with regex '{}'", string, re);
+            None
+        }
+    }
+}
+
+fn extract_string(string: String) -> Option<String> {
+    Some(string)
+}
+
+fn extract_string_with_unit(string: String) -> Specification<String> {
+    let splits = split_string(string);
+    let amount_str = splits.first()?;
+    let unit = splits.get(1)?;
+    Some((String::from(amount_str), String::from(unit)))
+}
+
+fn extract_f32(string: String) -> Option<f32> {
+    let amount = string.parse::<f32>().ok()?;
+    Some(amount)
+}
+
+fn extract_f32_with_unit(string: String) -> Specification<f32> {
+    let (amount_str, unit) = extract_string_with_unit(string)?;
+    let amount = amount_str.parse::<f32>().ok()?;
+    Some((amount, unit))
+}
+
+fn extract_u8(string: String) -> Option<u8> {
+    let amount = string.parse::<u8>().ok()?;
+    Some(amount)
+}
+
+fn extract_u16_with_unit(string: String) -> Specification<u16> {
+    let (amount_str, unit) = extract_string_with_unit(string)?;
+    let amount = amount_str.parse::<u16>().ok()?;
+    Some((amount, unit))
+}
+
+fn extract_model_year(span: ElementRef) -> Result<String> {
+    let elem = element_within(span, &["span.Year", "span.modelyear"])?;
+    Ok(inner_text(elem)[..4].to_string())
+}
+
+fn extract_model_make(span: ElementRef) -> Result<String> {
+    let elem = element_within(span, &["span.manufacturer"])?;
+    Ok(elem.inner_html())
+}
+
+fn extract_model_name(span: ElementRef) -> Result<String> {
+    let elem = element_within(span, &["span.model.name"])?;
+    Ok(elem.inner_html())
+}
+
+fn extract_model_specifications_table(page: Page) -> Result<BTreeMap<String, String>> {
+    let mut table = BTreeMap::new();
+    for row in page.elements("table.specstable tbody tr") {
+        let th = element_within(row, &["th:not(.sechead)"]);
+
+        if let Ok(th) = th {
+            let th_text = inner_text(th);
+            if IGNORED_ROWS.contains(th_text.as_str()) {
+                continue;
+            };
+            let spec_name = lower_underscore(th_text);
+            if !spec_name.is_empty() {
+                let td = match element_within(row, &["td"]) {
+                    Ok(string) => sanitize_text(inner_text(string)),
+                    Err(_)    => {
+                        warn!("Unable to find `td` for `row`:\n{}", row.html());
+                        "".to_string()
+                    }
+                };
+                if USELESS_VALUES.contains(td.as_str()) {
+                    continue;
+                }
+
+                table.insert(spec_name, td);
+            }
+        };
+    }
+
+    Ok(table)
+}
+
+fn sanitize_text(string: String) -> String {
+    string.trim().replace("\n", ", ").replace("×", "x").replace("No information available", "")
+}
+
+fn lower_underscore(string: String) -> String {
+    string.to_lowercase().replace(" ", "_")
+}
@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use regex::Regex;
+use serde::Serialize;
+
+use super::Specification;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) enum Powertrain {
+    #[default]
+    Combustion,
+    Hybrid(ElectricDrive),
+    Electric(ElectricDrive)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct ElectricDrive {
+    pub(super) battery_capacity: Specification<f32>,
+    pub(super) range: Specification<u16>,
+    pub(super) motor_count: Option<u8>,
+    pub(super) motor_placement: Option<String>,
+    pub(super) ac_charging_rate: Specification<f32>,
+    pub(super) dc_charging_rate: Specification<f32>
+}
+
+impl Powertrain {
+    pub(super) fn has_combustion_engine(&self) -> bool {
+        !matches!(self, Powertrain::Electric(_))
+    }
+
+    pub(super) fn electric_drive(&mut self) -> Option<&mut ElectricDrive> {
+        match self {
+            Powertrain::Combustion      => None,
+            Powertrain::Hybrid(drive)   => Some(drive),
+            Powertrain::Electric(drive) => Some(drive)
+        }
+    }
+}
+
+// Decides the powertrain from the engine type, falling back to whether any EV-only rows are present.
+// A vehicle with EV rows and an engine capacity is treated as a hybrid.
+pub(super) fn detect_powertrain(map: &BTreeMap<String, String>, has_electric_keys: bool) -> Powertrain {
+    let engine_type = map.get("engine_type").map(|s| s.to_lowercase()).unwrap_or_default();
+    let has_displacement = map.contains_key("capacity");
+
+    if engine_type.contains("hybrid") || (has_electric_keys && has_displacement) {
+        Powertrain::Hybrid(ElectricDrive::default())
+    } else if engine_type.contains("electric") || has_electric_keys {
+        Powertrain::Electric(ElectricDrive::default())
+    } else {
+        Powertrain::Combustion
+    }
+}
+
+// Carfolio lists motors as e.g. "2" or "dual motor".
+pub(super) fn extract_motor_count(string: String) -> Option<u8> {
+    let lower = string.to_lowercase();
+    let re = Regex::new(r"(\d+)").unwrap();
+
+    if let Some(count) = re.captures(&lower).and_then(|caps| caps.get(1)?.as_str().parse::<u8>().ok()) {
+        return Some(count);
+    }
+
+    match lower.split_whitespace().next()? {
+        "single" => Some(1),
+        "dual"   => Some(2),
+        "tri"    => Some(3),
+        "quad"   => Some(4),
+        _        => {
+            warn!("Could not parse motor count from '{}'", string);
+            None
+        }
+    }
+}
+
+pub(super) fn extract_motor_placement(string: String) -> Option<String> {
+    let lower = string.to_lowercase();
+    let front = lower.contains("front");
+    let rear = lower.contains("rear");
+
+    match (front, rear) {
+        (true, true)  => Some(String::from("Front and Rear")),
+        (true, false) => Some(String::from("Front")),
+        (false, true) => Some(String::from("Rear")),
+        _             => {
+            warn!("Could not parse motor placement from '{}'", string);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(rows: &[(&str, &str)], has_electric_keys: bool) -> Powertrain {
+        let map = rows.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+        detect_powertrain(&map, has_electric_keys)
+    }
+
+    #[test]
+    fn detect_powertrain_reads_the_engine_type() {
+        assert_eq!(detect(&[("engine_type", "Petrol-electric hybrid")], false), Powertrain::Hybrid(ElectricDrive::default()));
+        assert_eq!(detect(&[("engine_type", "Electric")], false), Powertrain::Electric(ElectricDrive::default()));
+        assert_eq!(detect(&[("engine_type", "Spark-ignition 4-stroke")], false), Powertrain::Combustion);
+    }
+
+    #[test]
+    fn detect_powertrain_falls_back_to_electric_rows() {
+        assert_eq!(detect(&[], true), Powertrain::Electric(ElectricDrive::default()));
+        assert_eq!(detect(&[("capacity", "1798 cm3")], true), Powertrain::Hybrid(ElectricDrive::default()));
+        assert_eq!(detect(&[("capacity", "1798 cm3")], false), Powertrain::Combustion);
+    }
+
+    #[test]
+    fn has_combustion_engine_is_false_only_for_electric() {
+        assert!(Powertrain::Combustion.has_combustion_engine());
+        assert!(Powertrain::Hybrid(ElectricDrive::default()).has_combustion_engine());
+        assert!(!Powertrain::Electric(ElectricDrive::default()).has_combustion_engine());
+    }
+
+    #[test]
+    fn extract_motor_count_reads_numbers_and_words() {
+        assert_eq!(extract_motor_count(String::from("2")), Some(2));
+        assert_eq!(extract_motor_count(String::from("3 motors")), Some(3));
+        assert_eq!(extract_motor_count(String::from("Dual motor")), Some(2));
+        assert_eq!(extract_motor_count(String::from("single")), Some(1));
+        assert_eq!(extract_motor_count(String::from("several")), None);
+        assert_eq!(extract_motor_count(String::new()), None);
+    }
+
+    #[test]
+    fn extract_motor_placement_reads_front_and_rear() {
+        assert_eq!(extract_motor_placement(String::from("front and rear axle")).as_deref(), Some("Front and Rear"));
+        assert_eq!(extract_motor_placement(String::from("Rear")).as_deref(), Some("Rear"));
+        assert_eq!(extract_motor_placement(String::from("hub")), None);
+    }
+}
@@ -0,0 +1,43 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub(crate) enum Outcome {
+    Parsed,
+    // Parsed, but the values contradict each other.
+    Inconsistent,
+    Missing,
+    Unparseable,
+    Ignored
+}
+
+// Where a field's value came from, so parse quality can be audited across a whole dataset.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct Provenance {
+    raw: Option<String>,
+    extractor: &'static str,
+    outcome: Outcome
+}
+
+impl Provenance {
+    pub(super) fn new(raw: Option<String>, extractor: &'static str, parsed: bool) -> Provenance {
+        let outcome = match (&raw, parsed) {
+            (None, _)        => Outcome::Missing,
+            (Some(_), true)  => Outcome::Parsed,
+            (Some(_), false) => Outcome::Unparseable
+        };
+
+        Provenance { raw, extractor, outcome }
+    }
+
+    pub(super) fn inconsistent(raw: Option<String>, extractor: &'static str) -> Provenance {
+        Provenance { raw, extractor, outcome: Outcome::Inconsistent }
+    }
+
+    pub(super) fn ignored(raw: String) -> Provenance {
+        Provenance { raw: Some(raw), extractor: "", outcome: Outcome::Ignored }
+    }
+
+    pub(crate) fn outcome(&self) -> Outcome {
+        self.outcome
+    }
+}
@@ -0,0 +1,366 @@
+use std::fmt::Debug;
+use std::collections::BTreeMap;
+
+use super::Vehicle;
+use super::{extract_displacement, extract_f32, extract_f32_with_unit, extract_max_speed, extract_power,
+            extract_power_to_weight_ratio, extract_string, extract_torque, extract_u8, extract_u16_with_unit};
+use super::brakes::{extract_brake_types, set_brake_diameter, set_brake_types};
+use super::derived::{extract_specific_output, extract_specific_torque};
+use super::engine::{extract_bore_stroke, extract_engine_config, extract_ratio};
+use super::fuel_economy::{Standard, extract_fuel_figures, set_fuel_figures};
+use super::powertrain::{ElectricDrive, detect_powertrain, extract_motor_count, extract_motor_placement};
+use super::provenance::Provenance;
+use super::weight::extract_weight_distribution;
+use super::wheels::{extract_tyre, extract_wheel_size};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Applies {
+    Always,
+    Combustion,
+    Electric
+}
+
+// A row of carfolio's specification table, and how it ends up on a `Vehicle`.
+// Fields without a parser are known but deliberately ignored.
+pub(crate) struct Field {
+    key: &'static str,
+    aliases: &'static [&'static str],
+    target: &'static str,
+    extractor: &'static str,
+    applies: Applies,
+    parse: Option<fn(&mut Vehicle, String) -> bool>,
+    // Whether a parsed value is self-consistent, e.g. percentages that should sum to 100.
+    check: Option<fn(&Vehicle) -> bool>
+}
+
+impl Field {
+    fn new(key: &'static str, target: &'static str, extractor: &'static str, parse: fn(&mut Vehicle, String) -> bool) -> Field {
+        Field { key, aliases: &[], target, extractor, applies: Applies::Always, parse: Some(parse), check: None }
+    }
+
+    fn ignored(key: &'static str) -> Field {
+        Field { key, aliases: &[], target: "", extractor: "", applies: Applies::Always, parse: None, check: None }
+    }
+
+    fn aliases(self, aliases: &'static [&'static str]) -> Field {
+        Field { aliases, ..self }
+    }
+
+    fn combustion(self) -> Field {
+        Field { applies: Applies::Combustion, ..self }
+    }
+
+    fn electric(self) -> Field {
+        Field { applies: Applies::Electric, ..self }
+    }
+
+    fn check(self, check: fn(&Vehicle) -> bool) -> Field {
+        Field { check: Some(check), ..self }
+    }
+
+    fn keys(&self) -> impl Iterator<Item = &'static str> {
+        std::iter::once(self.key).chain(self.aliases.iter().cloned())
+    }
+
+    fn matches(&self, key: &str) -> bool {
+        self.keys().any(|k| k == key)
+    }
+
+    // Removes the field and all of its aliases from the map, so none of them end up in `extra_specs`.
+    // The primary key's value is preferred, then the aliases in order.
+    fn take(&self, map: &mut BTreeMap<String, String>) -> Option<String> {
+        let mut values = self.keys().filter_map(|key| map.remove(key).map(|value| (key, value)));
+        let (_, value) = values.next()?;
+
+        for (key, other) in values {
+            if other != value {
+                warn!("{} disagrees with {} and was dropped: '{}' vs '{}'", key, self.key, other, value);
+            }
+        }
+
+        Some(value)
+    }
+}
+
+// Registers a parsed field, spelling its target and extractor from the code that runs so
+// `--list-fields` and provenance can't drift from it:
+//   field!(key, setter(target, extractor))
+//   field!(key, setter(target, argument, extractor))
+//   field!(key, electric(target, extractor)) for a field of the electric drive
+macro_rules! field {
+    ($key:expr, electric($target:ident, $extractor:ident)) => {
+        Field::new($key, concat!("powertrain.", stringify!($target)), stringify!($extractor),
+                   |v, s| electric(v, |drive| set(&mut drive.$target, $extractor(s)))).electric()
+    };
+    ($key:expr, $setter:ident($target:ident, $extractor:ident)) => {
+        Field::new($key, stringify!($target), stringify!($extractor), |v, s| $setter(&mut v.$target, $extractor(s)))
+    };
+    ($key:expr, $setter:ident($target:ident, $argument:expr, $extractor:ident)) => {
+        Field::new($key, concat!(stringify!($target), "[", stringify!($argument), "]"), stringify!($extractor),
+                   |v, s| $setter(&mut v.$target, $argument, $extractor(s)))
+    };
+}
+
+lazy_static! {
+    static ref FIELDS: Vec<Field> = vec![
+        field!("aspiration", set(aspiration, extract_string)).combustion(),
+        field!("body_type", set(body_type, extract_string)),
+        field!("bore_×_stroke", set(bore_stroke, extract_bore_stroke)).combustion(),
+        field!("bore/stroke_ratio", set(bore_stroke_ratio, extract_ratio)).combustion(),
+        field!("brakes_f/r", set_brake_types(brakes, extract_brake_types)),
+        field!("front_brake_diameter", set_brake_diameter(brakes, "Front", extract_u16_with_unit)),
+        field!("rear_brake_diameter", set_brake_diameter(brakes, "Rear", extract_u16_with_unit)),
+        field!("carfolio.com_id", set(carfolio_id, extract_string)),
+        field!("cda", set(cda, extract_f32_with_unit)),
+        field!("compression_ratio", set(compression_ratio, extract_ratio)).combustion(),
+        field!("kerb_weight", set(curb_weight, extract_u16_with_unit)),
+        field!("capacity", set(displacement, extract_displacement)).combustion(),
+        field!("number_of_doors", set(door_count, extract_u8)),
+        field!("drag_coefficient", set(drag_coefficient, extract_f32)),
+        field!("drive_wheels", set(drive_wheel_config, extract_string)),
+        field!("engine_code", set(engine_code, extract_string)),
+        field!("cylinders", set(engine_config, extract_engine_config)),
+        field!("engine_construction", set(engine_construction, extract_string)),
+        field!("engine_coolant", set(engine_coolant, extract_string)),
+        field!("engine_layout", set(engine_layout, extract_string)),
+        field!("engine_manufacturer", set(engine_manufacturer, extract_string)),
+        field!("engine_position", set(engine_position, extract_string)),
+        field!("engine_type", set(engine_type, extract_string)),
+        field!("final_drive_ratio", set(final_drive_ratio, extract_f32)),
+        field!("us_mpg", set_fuel_figures(fuel_economy, Standard::UsMpg, extract_fuel_figures)),
+        field!("uk_mpg", set_fuel_figures(fuel_economy, Standard::UkMpg, extract_fuel_figures)),
+        field!("litres/100km", set_fuel_figures(fuel_economy, Standard::LitresPer100Km, extract_fuel_figures))
+            .aliases(&["fuel_consumption"]),
+        field!("km/litre", set_fuel_figures(fuel_economy, Standard::KmPerLitre, extract_fuel_figures)),
+        field!("frontal_area", set(frontal_area, extract_f32_with_unit)),
+        field!("fuel_tank_capacity", set(fuel_capacity, extract_f32_with_unit)),
+        field!("ground_clearance", set(ground_clearance, extract_u16_with_unit)),
+        field!("height", set(height, extract_u16_with_unit)),
+        field!("length", set(length, extract_u16_with_unit)),
+        field!("length:wheelbase_ratio", set(length_wheelbase_ratio, extract_ratio)),
+        field!("maximum_speed", set(max_speed, extract_max_speed)),
+        field!("maximum_power_output", assign(power, extract_power)),
+        field!("power-to-weight_ratio", set(power_to_weight_ratio, extract_power_to_weight_ratio)),
+        field!("battery_capacity", electric(battery_capacity, extract_f32_with_unit)),
+        field!("range", electric(range, extract_u16_with_unit)),
+        field!("electric_motors", electric(motor_count, extract_motor_count)),
+        field!("electric_motor_position", electric(motor_placement, extract_motor_placement)),
+        field!("charging_ac", electric(ac_charging_rate, extract_f32_with_unit)),
+        field!("charging_dc", electric(dc_charging_rate, extract_f32_with_unit)),
+        field!("specific_output", set(specific_output, extract_specific_output)).combustion(),
+        field!("specific_torque", set(specific_torque, extract_specific_torque)).combustion(),
+        field!("steering", set(steering_config, extract_string)),
+        field!("tyres_front", set_entry(tires, "Front", extract_tyre)),
+        field!("tyres_rear", set_entry(tires, "Rear", extract_tyre)),
+        field!("top_gear_ratio", set(top_gear_ratio, extract_f32)),
+        field!("maximum_torque", assign(torque, extract_torque)),
+        field!("track/tread_(front)", set_entry(track, "Front", extract_u16_with_unit)),
+        field!("track/tread_(rear)", set_entry(track, "Rear", extract_u16_with_unit)),
+        field!("gearbox", set(transmission, extract_string)),
+        field!("valve_gear", set(valve_config, extract_string)).combustion(),
+        field!("weight_distribution", set(weight_distribution, extract_weight_distribution))
+            .check(|v| v.weight_distribution.as_ref().is_none_or(|distribution| distribution.sums_to_100())),
+        field!("weight-to-power_ratio", set(weight_to_power_ratio, extract_f32_with_unit)),
+        field!("wheel_size_front", set_entry(wheel_size, "Front", extract_wheel_size)),
+        field!("wheel_size_rear", set_entry(wheel_size, "Rear", extract_wheel_size)),
+        field!("wheelbase", set(wheelbase, extract_u16_with_unit)),
+        field!("width", set(width, extract_u16_with_unit)),
+        field!("acceleration_0-60mph", set(zero_to_sixty, extract_f32_with_unit)),
+
+        Field::ignored("bmep_(brake_mean_effective_pressure)"),
+        Field::ignored("catalytic_converter"),
+        Field::ignored("compressor"),
+        Field::ignored("fuel_system"),
+        Field::ignored("intercooler"),
+        Field::ignored("maximum_power_output(sae_net)"),
+        Field::ignored("maximum_torque(sae_net)"),
+        Field::ignored("rac_rating"),
+        Field::ignored("specific_output(sae_net)"),
+        Field::ignored("specific_torque(sae_net)"),
+        Field::ignored("sump"),
+        Field::ignored("turns_lock-to-lock"),
+        Field::ignored("unitary_capacity")
+    ];
+}
+
+// Builds a `Vehicle` by running every registered field over the specification table.
+// Parsed rows are removed from the map; ignored and unknown rows are left behind.
+pub(super) fn parse_specifications(map: &mut BTreeMap<String, String>) -> Vehicle {
+    let has_electric_keys = FIELDS.iter()
+        .filter(|field| field.applies == Applies::Electric)
+        .any(|field| field.keys().any(|key| map.contains_key(key)));
+
+    let mut vehicle = Vehicle {
+        powertrain: detect_powertrain(map, has_electric_keys),
+        ..Default::default()
+    };
+    let combustion = vehicle.powertrain.has_combustion_engine();
+    let electric = vehicle.powertrain.electric_drive().is_some();
+
+    for field in FIELDS.iter() {
+        let parse = match field.parse {
+            Some(parse) => parse,
+            None        => {
+                if let Some(string) = field.keys().find_map(|key| map.get(key)) {
+                    vehicle.provenance.insert(field.key.to_string(), Provenance::ignored(string.clone()));
+                }
+                continue;
+            }
+        };
+
+        match field.applies {
+            // Electric vehicles have no use for combustion-only fields, so they are dropped without a parse warning.
+            Applies::Combustion if !combustion => {
+                if let Some(string) = field.take(map) {
+                    debug!("{} skipped for electric powertrain: {}", field.key, string);
+                    vehicle.provenance.insert(field.key.to_string(), Provenance::ignored(string));
+                }
+                continue;
+            },
+            Applies::Electric if !electric => continue,
+            _ => ()
+        }
+
+        let raw = field.take(map);
+        let string = raw.clone().unwrap_or_default();
+        debug!("{} unparsed: {}", field.key, string);
+
+        let parsed = parse(&mut vehicle, string);
+
+        let provenance = if !parsed {
+            warn!("{} was unable to be parsed", field.key);
+            Provenance::new(raw, field.extractor, parsed)
+        } else if field.check.is_some_and(|check| !check(&vehicle)) {
+            warn!("{} is inconsistent: {}", field.key, raw.as_deref().unwrap_or_default());
+            Provenance::inconsistent(raw, field.extractor)
+        } else {
+            Provenance::new(raw, field.extractor, parsed)
+        };
+
+        vehicle.provenance.insert(field.key.to_string(), provenance);
+    }
+
+    if let Some(fuel_economy) = vehicle.fuel_economy.as_mut() {
+        fuel_economy.derive_missing();
+    }
+
+    vehicle
+}
+
+pub(super) fn unused_keys(map: &BTreeMap<String, String>) -> Vec<(&String, &String)> {
+    map.iter()
+        .filter(|(key, value)| !value.is_empty() && !FIELDS.iter().any(|field| field.matches(key)))
+        .collect()
+}
+
+// A Markdown table of every carfolio field the scraper knows about.
+pub(crate) fn supported_fields() -> String {
+    let mut table = String::from("| Carfolio key | Aliases | Vehicle field | Extractor | Powertrain |\n|---|---|---|---|---|\n");
+
+    for field in FIELDS.iter() {
+        let (target, extractor) = match field.parse {
+            Some(_) => (field.target, field.extractor),
+            None    => ("(ignored)", "")
+        };
+        let applies = match field.applies {
+            Applies::Always     => "any",
+            Applies::Combustion => "combustion",
+            Applies::Electric   => "electric"
+        };
+
+        table.push_str(&format!("| {} | {} | {} | {} | {} |\n", field.key, field.aliases.join(", "), target, extractor, applies));
+    }
+
+    table
+}
+
+fn set<T: Debug>(slot: &mut Option<T>, value: Option<T>) -> bool {
+    debug!("parsed: {:?}", value);
+    *slot = value;
+    slot.is_some()
+}
+
+fn assign<T: Debug>(slot: &mut T, value: Option<T>) -> bool {
+    debug!("parsed: {:?}", value);
+
+    match value {
+        Some(value) => {
+            *slot = value;
+            true
+        },
+        None => false
+    }
+}
+
+fn set_entry<T: Debug>(map: &mut BTreeMap<String, Option<T>>, axle: &str, value: Option<T>) -> bool {
+    set(map.entry(axle.to_string()).or_insert(None), value)
+}
+
+fn electric<F: FnOnce(&mut ElectricDrive) -> bool>(vehicle: &mut Vehicle, parse: F) -> bool {
+    match vehicle.powertrain.electric_drive() {
+        Some(drive) => parse(drive),
+        None        => false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{Outcome, Powertrain};
+
+    fn parse(rows: &[(&str, &str)]) -> (Vehicle, BTreeMap<String, String>) {
+        let mut map = rows.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+        (parse_specifications(&mut map), map)
+    }
+
+    #[test]
+    fn parse_specifications_flags_weight_distribution_that_does_not_sum_to_100() {
+        let (vehicle, _) = parse(&[("weight_distribution", "60/50")]);
+
+        assert_eq!(vehicle.provenance["weight_distribution"].outcome(), Outcome::Inconsistent);
+    }
+
+    #[test]
+    fn parse_specifications_takes_every_alias_of_a_field() {
+        let (vehicle, map) = parse(&[("litres/100km", "8.1"), ("fuel_consumption", "8.3")]);
+
+        assert!(map.is_empty());
+        assert_eq!(vehicle.provenance["litres/100km"].outcome(), Outcome::Parsed);
+    }
+
+    #[test]
+    fn parse_specifications_reports_unparseable_power_and_torque() {
+        let (vehicle, _) = parse(&[("maximum_power_output", "garbage"), ("maximum_torque", "420 Nm (310 lb ft) @ 4500 rpm")]);
+
+        assert_eq!(vehicle.provenance["maximum_power_output"].outcome(), Outcome::Unparseable);
+        assert_eq!(vehicle.provenance["maximum_torque"].outcome(), Outcome::Parsed);
+        assert!(vehicle.power.is_empty());
+    }
+
+    #[test]
+    fn parse_specifications_ignores_combustion_fields_on_electric_vehicles() {
+        let (vehicle, map) = parse(&[("engine_type", "Electric"), ("compression_ratio", "10.5:1"), ("electric_motors", "2")]);
+
+        assert!(map.is_empty());
+        assert_eq!(vehicle.provenance["compression_ratio"].outcome(), Outcome::Ignored);
+        assert_eq!(vehicle.compression_ratio, None);
+        assert!(matches!(vehicle.powertrain, Powertrain::Electric(ElectricDrive { motor_count: Some(2), .. })));
+    }
+
+    #[test]
+    fn parse_specifications_skips_electric_fields_on_combustion_vehicles() {
+        let (vehicle, _) = parse(&[("engine_type", "Spark-ignition 4-stroke"), ("compression_ratio", "10.5:1")]);
+
+        assert_eq!(vehicle.compression_ratio, Some(10.5));
+        assert_eq!(vehicle.powertrain, Powertrain::Combustion);
+    }
+
+    #[test]
+    fn supported_fields_names_the_code_that_runs() {
+        let table = supported_fields();
+
+        assert!(table.contains("| maximum_power_output |  | power | extract_power | any |"));
+        assert!(table.contains("| tyres_front |  | tires[\"Front\"] | extract_tyre | any |"));
+        assert!(table.contains("| battery_capacity |  | powertrain.battery_capacity | extract_f32_with_unit | electric |"));
+    }
+}
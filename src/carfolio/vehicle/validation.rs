@@ -0,0 +1,172 @@
+use serde::Serialize;
+
+use super::{Specification, Vehicle};
+
+static ZERO_TO_SIXTY_RANGE: (f32, f32) = (1.5, 30.0);
+static MAX_DOOR_COUNT: u8 = 6;
+static POWER_TO_WEIGHT_TOLERANCE: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub(crate) enum Severity {
+    Warning,
+    Error
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct Violation {
+    rule: &'static str,
+    severity: Severity,
+    message: String
+}
+
+impl Violation {
+    fn new(rule: &'static str, severity: Severity, message: String) -> Violation {
+        Violation { rule, severity, message }
+    }
+
+    pub(crate) fn severity(&self) -> Severity {
+        self.severity
+    }
+}
+
+// Checks a parsed vehicle for physically implausible data, which usually means a carfolio data-entry error.
+pub(super) fn validate(vehicle: &Vehicle) -> Vec<Violation> {
+    let mut violations = vec![];
+
+    if let Some((wheelbase, length)) = same_unit(&vehicle.wheelbase, &vehicle.length) {
+        if wheelbase >= length {
+            violations.push(Violation::new("wheelbase_shorter_than_length", Severity::Error,
+                format!("wheelbase {} is not shorter than length {}", wheelbase, length)));
+        }
+    }
+
+    for (axle, track) in &vehicle.track {
+        if let Some((track, width)) = same_unit(track, &vehicle.width) {
+            if track >= width {
+                violations.push(Violation::new("track_narrower_than_width", Severity::Error,
+                    format!("{} track {} is not narrower than width {}", axle, track, width)));
+            }
+        }
+    }
+
+    if let Some((seconds, _)) = &vehicle.zero_to_sixty {
+        let (min, max) = ZERO_TO_SIXTY_RANGE;
+        if *seconds < min || *seconds > max {
+            violations.push(Violation::new("zero_to_sixty_in_range", Severity::Error,
+                format!("0-60 time {} s is outside {}-{} s", seconds, min, max)));
+        }
+    }
+
+    if let Some(door_count) = vehicle.door_count {
+        if door_count > MAX_DOOR_COUNT {
+            violations.push(Violation::new("door_count_in_range", Severity::Error,
+                format!("door count {} is outside 0-{}", door_count, MAX_DOOR_COUNT)));
+        }
+    }
+
+    if let Some(violation) = validate_power_to_weight(vehicle) {
+        violations.push(violation);
+    }
+
+    violations
+}
+
+fn validate_power_to_weight(vehicle: &Vehicle) -> Option<Violation> {
+    let (ratio, ratio_unit) = vehicle.power_to_weight_ratio.as_ref()?;
+    let (power, power_unit) = vehicle.power.get("Value")?.as_ref()?;
+    let (weight, weight_unit) = vehicle.curb_weight.as_ref()?;
+
+    if ratio_unit != "kW/tonne" || power_unit != "kW" || weight_unit != "kg" {
+        return None;
+    }
+
+    let expected = *power as f32 / (*weight as f32 / 1000.0);
+
+    if (ratio - expected).abs() > expected * POWER_TO_WEIGHT_TOLERANCE {
+        Some(Violation::new("power_to_weight_consistent", Severity::Warning,
+            format!("power-to-weight ratio {} kW/tonne differs from {:.1} kW/tonne calculated from power and curb weight", ratio, expected)))
+    } else {
+        None
+    }
+}
+
+fn same_unit<'a>(a: &'a Specification<u16>, b: &'a Specification<u16>) -> Option<(u16, u16)> {
+    match (a, b) {
+        (Some((a, a_unit)), Some((b, b_unit))) if a_unit == b_unit => Some((*a, *b)),
+        _                                                           => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec<T>(value: T, unit: &str) -> Specification<T> {
+        Some((value, unit.to_string()))
+    }
+
+    fn rules(vehicle: &Vehicle) -> Vec<(&'static str, Severity)> {
+        validate(vehicle).iter().map(|violation| (violation.rule, violation.severity)).collect()
+    }
+
+    #[test]
+    fn validate_accepts_a_vehicle_without_data() {
+        assert_eq!(rules(&Vehicle::default()), vec![]);
+    }
+
+    #[test]
+    fn validate_rejects_a_wheelbase_not_shorter_than_the_length() {
+        let vehicle = Vehicle { wheelbase: spec(4500, "mm"), length: spec(4500, "mm"), ..Vehicle::default() };
+        assert_eq!(rules(&vehicle), vec![("wheelbase_shorter_than_length", Severity::Error)]);
+
+        let vehicle = Vehicle { wheelbase: spec(2700, "mm"), length: spec(4500, "mm"), ..Vehicle::default() };
+        assert_eq!(rules(&vehicle), vec![]);
+    }
+
+    #[test]
+    fn validate_skips_dimensions_in_different_units() {
+        let vehicle = Vehicle { wheelbase: spec(2700, "mm"), length: spec(177, "in"), ..Vehicle::default() };
+        assert_eq!(rules(&vehicle), vec![]);
+    }
+
+    #[test]
+    fn validate_rejects_a_track_not_narrower_than_the_width() {
+        let mut vehicle = Vehicle { width: spec(1800, "mm"), ..Vehicle::default() };
+        vehicle.track.insert(String::from("Front"), spec(1550, "mm"));
+        vehicle.track.insert(String::from("Rear"), spec(1800, "mm"));
+
+        assert_eq!(rules(&vehicle), vec![("track_narrower_than_width", Severity::Error)]);
+    }
+
+    #[test]
+    fn validate_checks_the_zero_to_sixty_bounds() {
+        for (seconds, valid) in [(1.4, false), (1.5, true), (30.0, true), (30.1, false)] {
+            let vehicle = Vehicle { zero_to_sixty: spec(seconds, "s"), ..Vehicle::default() };
+            assert_eq!(rules(&vehicle).is_empty(), valid, "0-60 in {} s", seconds);
+        }
+    }
+
+    #[test]
+    fn validate_checks_the_door_count() {
+        assert_eq!(rules(&Vehicle { door_count: Some(6), ..Vehicle::default() }), vec![]);
+        assert_eq!(rules(&Vehicle { door_count: Some(7), ..Vehicle::default() }), vec![("door_count_in_range", Severity::Error)]);
+    }
+
+    #[test]
+    fn validate_warns_when_power_to_weight_disagrees_with_power_and_weight() {
+        let mut vehicle = Vehicle { curb_weight: spec(1000, "kg"), power_to_weight_ratio: spec(105.0, "kW/tonne"), ..Vehicle::default() };
+        vehicle.power.insert(String::from("Value"), spec(100, "kW"));
+        assert_eq!(rules(&vehicle), vec![]);
+
+        vehicle.power_to_weight_ratio = spec(120.0, "kW/tonne");
+        assert_eq!(rules(&vehicle), vec![("power_to_weight_consistent", Severity::Warning)]);
+    }
+
+    #[test]
+    fn validate_skips_power_to_weight_in_other_units() {
+        let mut vehicle = Vehicle { curb_weight: spec(2205, "lb"), power_to_weight_ratio: spec(120.0, "kW/tonne"), ..Vehicle::default() };
+        vehicle.power.insert(String::from("Value"), spec(100, "kW"));
+
+        assert_eq!(rules(&vehicle), vec![]);
+    }
+}
@@ -0,0 +1,75 @@
+use regex::Regex;
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct WeightDistribution {
+    front: f32,
+    rear: f32
+}
+
+impl WeightDistribution {
+    pub(super) fn sums_to_100(&self) -> bool {
+        (self.front + self.rear - 100.0).abs() <= 0.5
+    }
+}
+
+// Carfolio lists weight distribution as e.g. "52/48", "52:48", "F 52%, R 48%" or "48% rear".
+// Values are kept as listed; a single value is completed to 100.
+pub(super) fn extract_weight_distribution(string: String) -> Option<WeightDistribution> {
+    let lower = string.to_lowercase();
+    let numbers: Vec<f32> = Regex::new(r"(\d+(?:\.\d+)?)").unwrap()
+        .captures_iter(&lower)
+        .filter_map(|caps| caps.get(1)?.as_str().parse::<f32>().ok())
+        .collect();
+
+    let labels: Vec<String> = Regex::new(r"\b(front|rear|f|r)\b").unwrap()
+        .captures_iter(&lower)
+        .filter_map(|caps| Some(caps.get(1)?.as_str().to_string()))
+        .collect();
+    let rear_first = labels.first().is_some_and(|label| label.starts_with('r'));
+
+    let (front, rear) = match (numbers.first(), numbers.get(1)) {
+        (Some(&first), Some(&second)) if rear_first => (second, first),
+        (Some(&first), Some(&second))               => (first, second),
+        (Some(&first), None) if rear_first          => (100.0 - first, first),
+        (Some(&first), None)                        => (first, 100.0 - first),
+        _                                           => {
+            warn!("Could not parse weight distribution from '{}'", string);
+            return None;
+        }
+    };
+
+    Some(WeightDistribution { front, rear })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distribution(string: &str) -> (f32, f32) {
+        let distribution = extract_weight_distribution(String::from(string)).unwrap();
+        (distribution.front, distribution.rear)
+    }
+
+    #[test]
+    fn extract_weight_distribution_reads_front_rear_pairs() {
+        assert_eq!(distribution("52/48"), (52.0, 48.0));
+        assert_eq!(distribution("52:48"), (52.0, 48.0));
+        assert_eq!(distribution("F 52%, R 48%"), (52.0, 48.0));
+        assert_eq!(distribution("R 48%, F 52%"), (52.0, 48.0));
+    }
+
+    #[test]
+    fn extract_weight_distribution_completes_a_single_value() {
+        assert_eq!(distribution("48% rear"), (52.0, 48.0));
+        assert_eq!(distribution("52%"), (52.0, 48.0));
+    }
+
+    #[test]
+    fn extract_weight_distribution_keeps_values_that_do_not_sum_to_100() {
+        let distribution = extract_weight_distribution(String::from("60/50")).unwrap();
+
+        assert_eq!((distribution.front, distribution.rear), (60.0, 50.0));
+        assert!(!distribution.sums_to_100());
+    }
+}
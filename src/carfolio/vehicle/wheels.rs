@@ -0,0 +1,153 @@
+use regex::Regex;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub(crate) enum TyreConstruction {
+    Radial,
+    Diagonal,
+    BiasBelted
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct TyreSpec {
+    section_width: u16,
+    aspect_ratio: Option<u8>,
+    speed_rating: Option<String>,
+    construction: TyreConstruction,
+    rim_diameter: f32,
+    load_index: Option<u16>
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct WheelSize {
+    diameter: f32,
+    width: Option<f32>
+}
+
+// Carfolio lists tyres as e.g. "P245/40ZR18 97Y", "225/45 R17 (91W)" or "185 R14".
+// Only the first tyre is used when several are listed for the same axle.
+pub(super) fn extract_tyre(string: String) -> Option<TyreSpec> {
+    let re = Regex::new(r"(?:P|LT|ST|T)?(\d{3})\s*(?:/\s*(\d{2,3}))?\s*([HVWYZ])?(R|D|B)\s*(\d{2}(?:\.\d)?)(?:\s*\(?(\d{2,3})(?:/\d{2,3})?\s*([A-Z]\d?)\)?)?").unwrap();
+
+    let caps = match re.captures(&string) {
+        Some(caps) => caps,
+        None       => {
+            warn!("Could not parse tyre size from '{}' with regex '{}'", string, re);
+            return None;
+        }
+    };
+
+    let section_width = caps.get(1)?.as_str().parse::<u16>().ok()?;
+    let aspect_ratio = caps.get(2).and_then(|m| m.as_str().parse::<u8>().ok());
+    let construction = match caps.get(4)?.as_str() {
+        "R" => TyreConstruction::Radial,
+        "D" => TyreConstruction::Diagonal,
+        _   => TyreConstruction::BiasBelted
+    };
+    let rim_diameter = caps.get(5)?.as_str().parse::<f32>().ok()?;
+    let load_index = caps.get(6).and_then(|m| m.as_str().parse::<u16>().ok());
+
+    // A service description ("97Y") takes precedence over a speed letter embedded in the size ("ZR").
+    let speed_rating = caps.get(7).or_else(|| caps.get(3)).map(|m| m.as_str().to_string());
+
+    Some(TyreSpec {
+        section_width,
+        aspect_ratio,
+        speed_rating,
+        construction,
+        rim_diameter,
+        load_index
+    })
+}
+
+// Carfolio lists wheels as e.g. "8.5J x 19", "19 x 8.5" or "17 in".
+pub(super) fn extract_wheel_size(string: String) -> Option<WheelSize> {
+    let re = Regex::new(r"(\d+(?:\.\d+)?)\s*(J)?\s*x\s*(\d+(?:\.\d+)?)\s*(J)?").unwrap();
+
+    if let Some(caps) = re.captures(&string) {
+        let first = caps.get(1)?.as_str().parse::<f32>().ok()?;
+        let second = caps.get(3)?.as_str().parse::<f32>().ok()?;
+
+        let (width, diameter) = if caps.get(2).is_some() {
+            (first, second)
+        } else if caps.get(4).is_some() {
+            (second, first)
+        } else if first < second {
+            (first, second)
+        } else {
+            (second, first)
+        };
+
+        return Some(WheelSize { diameter, width: Some(width) });
+    }
+
+    let re = Regex::new(r#"(\d+(?:\.\d+)?)\s*(?:in|inch|")"#).unwrap();
+
+    match re.captures(&string) {
+        Some(caps) => {
+            let diameter = caps.get(1)?.as_str().parse::<f32>().ok()?;
+            Some(WheelSize { diameter, width: None })
+        },
+        None => {
+            warn!("Could not parse wheel size from '{}' with regex '{}'", string, re);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tyre(string: &str) -> TyreSpec {
+        extract_tyre(String::from(string)).unwrap()
+    }
+
+    fn wheel(string: &str) -> WheelSize {
+        extract_wheel_size(String::from(string)).unwrap()
+    }
+
+    #[test]
+    fn extract_tyre_reads_a_full_size_and_service_description() {
+        assert_eq!(tyre("P245/40ZR18 97Y"), TyreSpec {
+            section_width: 245,
+            aspect_ratio: Some(40),
+            speed_rating: Some(String::from("Y")),
+            construction: TyreConstruction::Radial,
+            rim_diameter: 18.0,
+            load_index: Some(97)
+        });
+    }
+
+    #[test]
+    fn extract_tyre_reads_a_bracketed_service_description() {
+        let spec = tyre("225/45 R17 (91W)");
+
+        assert_eq!((spec.section_width, spec.aspect_ratio, spec.rim_diameter), (225, Some(45), 17.0));
+        assert_eq!((spec.load_index, spec.speed_rating.as_deref()), (Some(91), Some("W")));
+    }
+
+    #[test]
+    fn extract_tyre_reads_a_size_without_aspect_ratio() {
+        let spec = tyre("185 R14");
+
+        assert_eq!((spec.section_width, spec.aspect_ratio, spec.rim_diameter), (185, None, 14.0));
+        assert_eq!((spec.load_index, spec.speed_rating), (None, None));
+    }
+
+    #[test]
+    fn extract_tyre_rejects_unrecognised_sizes() {
+        assert_eq!(extract_tyre(String::from("run-flat")), None);
+    }
+
+    #[test]
+    fn extract_wheel_size_reads_width_and_diameter_in_either_order() {
+        assert_eq!(wheel("8.5J x 19"), WheelSize { diameter: 19.0, width: Some(8.5) });
+        assert_eq!(wheel("19 x 8.5"), WheelSize { diameter: 19.0, width: Some(8.5) });
+    }
+
+    #[test]
+    fn extract_wheel_size_reads_a_bare_diameter() {
+        assert_eq!(wheel("17 in"), WheelSize { diameter: 17.0, width: None });
+    }
+}
@@ -3,20 +3,42 @@ use scraper::element_ref::ElementRef;
 pub(crate) type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub(crate) enum Error {
     // Errors from crates
+    IoError(std::io::Error),
+    JsonError(serde_json::Error),
     ReqwestError(reqwest::Error),
     ParseIntError(std::num::ParseIntError),
+    RegexError(regex::Error),
     // Errors from this crate
-    ScraperError(ScraperErrorKind)
+    ScraperError(ScraperErrorKind),
+    ConfigError(String),
+    HttpError { status: reqwest::StatusCode, url: String },
+    ArchiveError(String),
+    NotArchived { url: String },
+    ReadTimeout { url: String }
 }
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub(crate) enum ScraperErrorKind {
     ElementError(ElementNotFound),
     AttributeError(AttributeNotFound),
 }
 
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Error {
+        Error::IoError(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Error {
+        Error::JsonError(err)
+    }
+}
+
 impl From<reqwest::Error> for Error {
     fn from(err: reqwest::Error) -> Error {
         Error::ReqwestError(err)
@@ -29,14 +51,28 @@ impl From<std::num::ParseIntError> for Error {
     }
 }
 
+impl From<regex::Error> for Error {
+    fn from(err: regex::Error) -> Error {
+        Error::RegexError(err)
+    }
+}
+
 impl std::error::Error for Error {}
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match *self {
+            Error::IoError(ref err) => err.fmt(f),
+            Error::JsonError(ref err) => err.fmt(f),
             Error::ReqwestError(ref err) => err.fmt(f),
             Error::ParseIntError(ref err) => err.fmt(f),
-            Error::ScraperError(ref err) => write!(f, "{}", err)
+            Error::RegexError(ref err) => err.fmt(f),
+            Error::ScraperError(ref err) => write!(f, "{}", err),
+            Error::ConfigError(ref message) => write!(f, "{}", message),
+            Error::HttpError { status, ref url } => write!(f, "HTTP {} for {}", status, url),
+            Error::ArchiveError(ref message) => write!(f, "{}", message),
+            Error::NotArchived { ref url } => write!(f, "{} is not in the WARC archive", url),
+            Error::ReadTimeout { ref url } => write!(f, "{} stopped sending data within the read timeout", url)
         }
     }
 }
@@ -53,8 +89,8 @@ impl ElementNotFound {
         let elements = elements.join(", ");
         
         ElementNotFound {
-            html: String::from(html),
-            elements: elements
+            html,
+            elements
         }
     }
 }
@@ -70,7 +106,7 @@ impl AttributeNotFound {
         let element = String::from(element.html().trim());
         
         AttributeNotFound {
-            element: element,
+            element,
             attribute: String::from(attribute)
         }
     }
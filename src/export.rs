@@ -0,0 +1,31 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+use logging_timer::time;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::error::Result;
+
+#[time("info")]
+pub(crate) fn write_json<T: Serialize>(path: &str, data: &T) -> Result<()> {
+    info!("Exporting to {}", path);
+
+    let writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(writer, data)?;
+
+    Ok(())
+}
+
+// Reads back a previous export, if there is one.
+pub(crate) fn read_json<T: DeserializeOwned>(path: &str) -> Result<Option<T>> {
+    if !Path::new(path).exists() {
+        return Ok(None);
+    }
+
+    info!("Importing from {}", path);
+
+    let reader = BufReader::new(File::open(path)?);
+    Ok(Some(serde_json::from_reader(reader)?))
+}
@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT};
+use reqwest::redirect::Policy;
+use reqwest::{Client, Proxy};
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
+
+static DEFAULT_USER_AGENT: &str = concat!("carfolio-scraper/", env!("CARGO_PKG_VERSION"));
+// Same as reqwest's own default.
+const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+static HTTP: OnceLock<Http> = OnceLock::new();
+
+struct Http {
+    client: Client,
+    user_agent: String,
+    // Sent with every request, including the User-Agent.
+    headers: HeaderMap,
+    // Redirects are followed by `Page::get_html`, so every hop can be archived.
+    max_redirects: usize,
+    // Enforced by `Page::get_html`, since reqwest only has a timeout for the whole exchange.
+    read_timeout: Option<Duration>
+}
+
+// How the HTTP client is built, from the config file and the CLI. Timeouts are in seconds.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct ClientConfig {
+    pub(crate) user_agent: Option<String>,
+    pub(crate) connect_timeout: Option<u64>,
+    // Covers the whole exchange, from connecting to reading the body.
+    pub(crate) timeout: Option<u64>,
+    // How long the server can go without sending anything, however long the whole exchange takes.
+    pub(crate) read_timeout: Option<u64>,
+    pub(crate) http_proxy: Option<String>,
+    pub(crate) https_proxy: Option<String>,
+    pub(crate) headers: BTreeMap<String, String>,
+    pub(crate) max_redirects: Option<usize>
+}
+
+impl ClientConfig {
+    pub(crate) fn from_file(path: &str) -> Result<ClientConfig> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    // Settings given in `overrides` (e.g. on the CLI) win over the ones in `self`.
+    pub(crate) fn merge(mut self, overrides: ClientConfig) -> ClientConfig {
+        self.headers.extend(overrides.headers);
+
+        ClientConfig {
+            user_agent: overrides.user_agent.or(self.user_agent),
+            connect_timeout: overrides.connect_timeout.or(self.connect_timeout),
+            timeout: overrides.timeout.or(self.timeout),
+            read_timeout: overrides.read_timeout.or(self.read_timeout),
+            http_proxy: overrides.http_proxy.or(self.http_proxy),
+            https_proxy: overrides.https_proxy.or(self.https_proxy),
+            headers: self.headers,
+            max_redirects: overrides.max_redirects.or(self.max_redirects)
+        }
+    }
+}
+
+// Builds the shared client. Must be called before the first request, or the defaults are used.
+pub(crate) fn init(config: &ClientConfig) -> Result<()> {
+    let user_agent = config.user_agent.clone().unwrap_or_else(|| String::from(DEFAULT_USER_AGENT));
+    let mut builder = Client::builder().user_agent(user_agent.as_str()).redirect(Policy::none());
+
+    if let Some(seconds) = config.connect_timeout {
+        builder = builder.connect_timeout(Duration::from_secs(seconds));
+    }
+    if let Some(seconds) = config.timeout {
+        builder = builder.timeout(Duration::from_secs(seconds));
+    }
+    if let Some(proxy) = &config.http_proxy {
+        builder = builder.proxy(Proxy::http(proxy.as_str())?);
+    }
+    if let Some(proxy) = &config.https_proxy {
+        builder = builder.proxy(Proxy::https(proxy.as_str())?);
+    }
+
+    let mut headers = HeaderMap::new();
+    for (name, value) in &config.headers {
+        let name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| Error::ConfigError(format!("Invalid header name '{}': {}", name, e)))?;
+        let value = HeaderValue::from_str(value).map_err(|e| Error::ConfigError(format!("Invalid value for header '{}': {}", name, e)))?;
+        headers.insert(name, value);
+    }
+
+    let client = builder.default_headers(headers.clone()).build()?;
+    headers.insert(USER_AGENT, HeaderValue::from_str(&user_agent).map_err(|e| Error::ConfigError(format!("Invalid User-Agent '{}': {}", user_agent, e)))?);
+
+    let max_redirects = config.max_redirects.unwrap_or(DEFAULT_MAX_REDIRECTS);
+    let read_timeout = config.read_timeout.map(Duration::from_secs);
+    let http = Http { client, user_agent, headers, max_redirects, read_timeout };
+
+    if HTTP.set(http).is_err() {
+        warn!("HTTP client was already in use, ignoring the new configuration");
+    }
+
+    Ok(())
+}
+
+fn http() -> &'static Http {
+    HTTP.get_or_init(|| {
+        let mut headers = HeaderMap::new();
+        headers.insert(USER_AGENT, HeaderValue::from_static(DEFAULT_USER_AGENT));
+
+        Http {
+            client: Client::builder().user_agent(DEFAULT_USER_AGENT).redirect(Policy::none()).build().unwrap(),
+            user_agent: String::from(DEFAULT_USER_AGENT),
+            headers,
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            read_timeout: None
+        }
+    })
+}
+
+pub(crate) fn client() -> &'static Client {
+    &http().client
+}
+
+pub(crate) fn user_agent() -> &'static str {
+    &http().user_agent
+}
+
+pub(crate) fn default_headers() -> &'static HeaderMap {
+    &http().headers
+}
+
+pub(crate) fn max_redirects() -> usize {
+    http().max_redirects
+}
+
+pub(crate) fn read_timeout() -> Option<Duration> {
+    http().read_timeout
+}
+
+// Parses a CLI header of the form "Name: value".
+pub(crate) fn parse_header(header: &str) -> Result<(String, String)> {
+    match header.split_once(':') {
+        Some((name, value)) => Ok((name.trim().to_string(), value.trim().to_string())),
+        None                => Err(Error::ConfigError(format!("Expected a header of the form 'Name: value', got '{}'", header)))
+    }
+}
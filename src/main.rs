@@ -6,24 +6,200 @@ extern crate lazy_static;
 
 extern crate pretty_env_logger;
 
+use std::future::Future;
+
 use logging_timer::time;
+use reqwest::{StatusCode, Url};
+use reqwest::header::{ETAG, HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, LOCATION};
+use structopt::StructOpt;
 
 use scraper::html::Html;
 use scraper::Selector;
 use scraper::element_ref::ElementRef;
 
+mod cache;
 mod error;
 mod carfolio;
+mod export;
+mod http;
+mod warc;
 
-use error::Result;
+use error::{Error, Result};
 use error::Error::ScraperError;
 use error::ScraperErrorKind::{ElementError, AttributeError};
 use error::{ElementNotFound, AttributeNotFound};
 
+#[derive(StructOpt)]
+#[structopt(about = "Scrapes vehicle specifications from carfolio.com")]
+struct Opt {
+    /// Print a Markdown table of the supported carfolio fields and exit
+    #[structopt(long)]
+    list_fields: bool,
+
+    /// Reject vehicles that fail an error-level validation rule
+    #[structopt(long)]
+    strict: bool,
+
+    /// Only crawl makes matching these globs (or "regex:" patterns); defaults to a preset list
+    #[structopt(long, number_of_values = 1)]
+    allow: Vec<String>,
+
+    /// Never crawl makes matching these globs (or "regex:" patterns)
+    #[structopt(long, number_of_values = 1)]
+    deny: Vec<String>,
+
+    /// Only scrape listings sold in any of these markets
+    #[structopt(long = "market", default_value = "US", number_of_values = 1)]
+    markets: Vec<String>,
+
+    /// Only scrape listings from this model year onward
+    #[structopt(long)]
+    from_year: Option<u16>,
+
+    /// Only scrape listings up to and including this model year
+    #[structopt(long)]
+    to_year: Option<u16>,
+
+    /// Only collect makes and model listings, without fetching vehicle pages
+    #[structopt(long)]
+    catalog_only: bool,
+
+    /// Only fetch listings that aren't already in the previous vehicles.json, and write what was added to delta.json
+    #[structopt(long)]
+    incremental: bool,
+
+    /// Fraction of already-known listings to fetch again during an incremental crawl
+    #[structopt(long, default_value = "0")]
+    reverify_rate: f64,
+
+    /// Read HTTP client settings from this JSON file; the flags below override it
+    #[structopt(long)]
+    config: Option<String>,
+
+    /// User-Agent sent with every request
+    #[structopt(long)]
+    user_agent: Option<String>,
+
+    /// Seconds to wait for a connection
+    #[structopt(long)]
+    connect_timeout: Option<u64>,
+
+    /// Seconds to wait for a whole request, including reading the response
+    #[structopt(long)]
+    timeout: Option<u64>,
+
+    /// Seconds to wait for the server to send more of the response
+    #[structopt(long)]
+    read_timeout: Option<u64>,
+
+    /// Proxy for http:// URLs
+    #[structopt(long)]
+    http_proxy: Option<String>,
+
+    /// Proxy for https:// URLs
+    #[structopt(long)]
+    https_proxy: Option<String>,
+
+    /// Extra header sent with every request, as "Name: value"
+    #[structopt(long = "header", number_of_values = 1)]
+    headers: Vec<String>,
+
+    /// Maximum number of redirects to follow
+    #[structopt(long)]
+    max_redirects: Option<usize>,
+
+    /// Append every HTTP request and response to this WARC file
+    #[structopt(long, conflicts_with = "offline")]
+    warc: Option<String>,
+
+    /// Rebuild the dataset from this WARC file, without any network access
+    #[structopt(long)]
+    offline: Option<String>
+}
+
 fn main() -> Result<()> {
     pretty_env_logger::init();
 
-    match carfolio::scrape() {
+    let opt = Opt::from_args();
+
+    if opt.list_fields {
+        print!("{}", carfolio::supported_fields());
+        return Ok(());
+    }
+
+    let file_config = match &opt.config {
+        Some(path) => http::ClientConfig::from_file(path)?,
+        None       => http::ClientConfig::default()
+    };
+    let cli_config = http::ClientConfig {
+        user_agent: opt.user_agent.clone(),
+        connect_timeout: opt.connect_timeout,
+        timeout: opt.timeout,
+        read_timeout: opt.read_timeout,
+        http_proxy: opt.http_proxy.clone(),
+        https_proxy: opt.https_proxy.clone(),
+        headers: opt.headers.iter().map(|header| http::parse_header(header)).collect::<Result<_>>()?,
+        max_redirects: opt.max_redirects
+    };
+    http::init(&file_config.merge(cli_config))?;
+
+    if let Some(path) = &opt.warc {
+        warc::record_to(path)?;
+    }
+    if let Some(path) = &opt.offline {
+        warc::replay_from(path)?;
+    }
+
+    let previous_vehicles = if opt.incremental {
+        export::read_json::<Vec<serde_json::Value>>("vehicles.json")?
+    } else {
+        None
+    };
+
+    let options = carfolio::Options {
+        strict: opt.strict,
+        makes: carfolio::MakeFilter::new(&opt.allow, &opt.deny)?,
+        markets: opt.markets.iter().cloned().collect(),
+        from_year: opt.from_year,
+        to_year: opt.to_year,
+        catalog_only: opt.catalog_only,
+        incremental: previous_vehicles.as_ref().map(|previous| carfolio::Incremental::new(previous, opt.reverify_rate))
+    };
+
+    let result = carfolio::scrape(&options).and_then(|crawl| {
+        // Nothing is discovered when the makes page couldn't be read, and an empty list would make every make look new next run.
+        if crawl.discovered_makes.is_empty() {
+            warn!("No makes discovered, keeping the previous discovered_makes.json");
+        } else {
+            if let Some(previous) = export::read_json::<Vec<String>>("discovered_makes.json")? {
+                for name in carfolio::new_makes(&previous, &crawl.discovered_makes) {
+                    info!("New Make since the previous run: {}", name);
+                }
+            }
+
+            export::write_json("discovered_makes.json", &crawl.discovered_makes)?;
+        }
+
+        export::write_json("makes.json", &crawl.makes)?;
+        export::write_json("listings.json", &crawl.listings)?;
+
+        if options.catalog_only {
+            return Ok(());
+        }
+
+        match previous_vehicles {
+            Some(previous) => {
+                let (dataset, delta) = carfolio::merge_incremental(previous, &crawl)?;
+                export::write_json("vehicles.json", &dataset)?;
+                export::write_json("delta.json", &delta)?;
+            },
+            None => export::write_json("vehicles.json", &crawl.vehicles)?
+        }
+
+        export::write_json("coverage.json", &crawl.coverage)
+    });
+
+    match result {
         Err(e) => {
             error!("{}", e);
             Ok(())
@@ -32,35 +208,152 @@ fn main() -> Result<()> {
     }
 }
 
-lazy_static! {
-    static ref REQWEST_CLIENT: reqwest::Client = reqwest::Client::new();
-}
-
 struct Page {
     url: String,
-    html: Html
+    html: Html,
+    // The server answered 304, so this is the cached body from a previous run.
+    not_modified: bool
 }
 
 impl Page {
-    fn new(url: &str) -> Page {
-        match Self::get_html(url) {
-            Ok(html) => Self { url: String::from(url), html: html },
-            Err(e)   => panic!("{}", e)
-        }
+    fn new(url: &str) -> Result<Page> {
+        let (body, not_modified) = Self::get_html(url)?;
+        Ok(Self { not_modified, ..Self::from_html(url, &body) })
     }
 
+    fn from_html(url: &str, body: &str) -> Page {
+        Self { url: String::from(url), html: Html::parse_document(body), not_modified: false }
+    }
+
+    // Revalidates against the cached copy when there is one, and reuses its body on a 304.
+    // Redirects are followed here rather than by reqwest, so every hop gets archived under its own URL.
+    // Offline, pages come from the replayed WARC file instead.
     #[tokio::main]
     #[time("info")]
-    async fn get_html(url: &str) -> Result<Html> {
+    async fn get_html(url: &str) -> Result<(String, bool)> {
+        if warc::is_replaying() {
+            info!("Replaying HTML for {}", url);
+            return Self::replay_html(url);
+        }
+
         info!("Fetching HTML from {}", url);
-        let resp = REQWEST_CLIENT.get(url).send().await?;
-        let body = resp.text().await?;
-        
-        Ok(Html::parse_document(&body))
+
+        // An archive has to hold every body, so nothing is revalidated while recording one.
+        let cached = if warc::is_recording() { None } else { cache::load(url) };
+
+        // Every hop is revalidated, since the cached body is the one at the end of the redirects.
+        let get = |url: &str| {
+            let mut request = http::client().get(url);
+            if let Some(cached) = &cached {
+                if let Some(etag) = &cached.etag {
+                    request = request.header(IF_NONE_MATCH, etag.as_str());
+                }
+                if let Some(last_modified) = &cached.last_modified {
+                    request = request.header(IF_MODIFIED_SINCE, last_modified.as_str());
+                }
+            }
+            request
+        };
+
+        let mut request = get(url);
+        let mut redirects = 0;
+
+        let (final_url, status, response_headers, bytes) = loop {
+            let built = request.build()?;
+            let method = built.method().clone();
+            let request_url = built.url().clone();
+            let mut request_headers = http::default_headers().clone();
+            request_headers.extend(built.headers().clone());
+
+            let mut resp = read(&request_url, http::client().execute(built)).await?;
+            let status = resp.status();
+            let version = resp.version();
+            let response_headers = resp.headers().clone();
+
+            let mut bytes = vec![];
+            while let Some(chunk) = read(&request_url, resp.chunk()).await? {
+                bytes.extend_from_slice(&chunk);
+            }
+
+            warc::write_exchange(&warc::Exchange {
+                method: &method,
+                url: &request_url,
+                request_headers: &request_headers,
+                version,
+                status,
+                response_headers: &response_headers,
+                body: &bytes
+            })?;
+
+            let location = response_headers.get(LOCATION).and_then(|value| value.to_str().ok());
+
+            match redirect_target(&request_url, status, location) {
+                Some(next) if redirects < http::max_redirects() => {
+                    info!("Following redirect from {} to {}", request_url, next);
+                    redirects += 1;
+                    request = get(next.as_str());
+                },
+                _ => break (request_url, status, response_headers, bytes)
+            }
+        };
+
+        if status == StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                info!("Not modified since the last fetch: {}", url);
+                return Ok((cached.body, true));
+            }
+        }
+
+        // Error pages would otherwise be parsed, and fail later as missing elements.
+        // A redirect still left at this point has no usable Location, or is one too many.
+        if !status.is_success() {
+            return Err(Error::HttpError { status, url: final_url.to_string() });
+        }
+
+        let header = |name| response_headers.get(name).and_then(|value: &HeaderValue| value.to_str().ok()).map(String::from);
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+        let body = String::from_utf8_lossy(&bytes).into_owned();
+
+        if etag.is_some() || last_modified.is_some() {
+            let page = cache::CachedPage { url: url.to_string(), etag, last_modified, body };
+
+            if let Err(e) = cache::store(url, &page) {
+                warn!("Unable to cache {}: {}", url, e);
+            }
+
+            return Ok((page.body, false));
+        }
+
+        Ok((body, false))
     }
 
-    fn elements(&self, selector_str: &str) -> Vec<ElementRef> {
-        let selector = Selector::parse(&selector_str).unwrap();
+    // Follows archived redirects the same way `get_html` follows live ones.
+    fn replay_html(url: &str) -> Result<(String, bool)> {
+        let mut target = url.to_string();
+        let mut redirects = 0;
+
+        loop {
+            let replayed = match warc::replayed(&target) {
+                Some(replayed) => replayed,
+                None           => return Err(Error::NotArchived { url: target })
+            };
+
+            let next = Url::parse(&target).ok().and_then(|current| redirect_target(&current, replayed.status, replayed.location.as_deref()));
+
+            match next {
+                Some(next) if redirects < http::max_redirects() => {
+                    redirects += 1;
+                    target = next.to_string();
+                },
+                _ if replayed.status.is_success() => return Ok((replayed.body.clone(), false)),
+                _                                 => return Err(Error::HttpError { status: replayed.status, url: target })
+            }
+        }
+    }
+
+    fn elements(&self, selector_str: &str) -> Vec<ElementRef<'_>> {
+        let selector = Selector::parse(selector_str).unwrap();
         
         let results: Vec<ElementRef> = self.html.select(&selector).collect();
 
@@ -74,7 +367,7 @@ impl Page {
 
 fn element_within<'a>(element: ElementRef<'a>, selectors: &[&str]) -> Result<ElementRef<'a>> {
     let elem = selectors.iter().find_map(|selector| {
-        let selector = Selector::parse(&selector).unwrap();
+        let selector = Selector::parse(selector).unwrap();
         element.select(&selector).next()
     });
 
@@ -84,6 +377,11 @@ fn element_within<'a>(element: ElementRef<'a>, selectors: &[&str]) -> Result<Ele
     }
 }
 
+fn elements_within<'a>(element: ElementRef<'a>, selector: &str) -> Vec<ElementRef<'a>> {
+    let selector = Selector::parse(selector).unwrap();
+    element.select(&selector).collect()
+}
+
 fn element_attr(element: ElementRef, selector: &str, attr: &str) -> Result<String> {
     let elem = element_within(element, &[selector])?;
 
@@ -99,5 +397,25 @@ fn inner_html(element: ElementRef, selector: &str) -> Result<String> {
 }
 
 fn inner_text(element: ElementRef) -> String {
-    element.text().into_iter().collect::<Vec<&str>>().join("")
+    element.text().collect::<Vec<&str>>().join("")
+}
+
+// Waits for the next part of a response, failing if the server goes quiet for longer than the read timeout.
+async fn read<T>(url: &Url, future: impl Future<Output = reqwest::Result<T>>) -> Result<T> {
+    match http::read_timeout() {
+        Some(limit) => match tokio::time::timeout(limit, future).await {
+            Ok(result) => Ok(result?),
+            Err(_)     => Err(Error::ReadTimeout { url: url.to_string() })
+        },
+        None => Ok(future.await?)
+    }
+}
+
+// Where a 3xx response sends the client, resolved against the URL that returned it.
+fn redirect_target(url: &Url, status: StatusCode, location: Option<&str>) -> Option<Url> {
+    if !status.is_redirection() || status == StatusCode::NOT_MODIFIED {
+        return None;
+    }
+
+    url.join(location?).ok()
 }
@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::sync::{Mutex, OnceLock};
+
+use chrono::{SecondsFormat, Utc};
+use reqwest::header::{HeaderMap, TRANSFER_ENCODING};
+use reqwest::{Method, StatusCode, Url, Version};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+
+static WRITER: OnceLock<Mutex<File>> = OnceLock::new();
+static REPLAY: OnceLock<BTreeMap<String, Replayed>> = OnceLock::new();
+
+// One HTTP exchange made by `Page::get_html`.
+pub(crate) struct Exchange<'a> {
+    pub(crate) method: &'a Method,
+    pub(crate) url: &'a Url,
+    pub(crate) request_headers: &'a HeaderMap,
+    pub(crate) version: Version,
+    pub(crate) status: StatusCode,
+    pub(crate) response_headers: &'a HeaderMap,
+    pub(crate) body: &'a [u8]
+}
+
+// A response read back from an archive.
+pub(crate) struct Replayed {
+    pub(crate) status: StatusCode,
+    // Kept so archived redirects can be followed.
+    pub(crate) location: Option<String>,
+    pub(crate) body: String
+}
+
+// Appends every exchange to the WARC file at `path`, starting it with a warcinfo record if it's new.
+pub(crate) fn record_to(path: &str) -> Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    if file.metadata()?.len() == 0 {
+        let info = format!("software: carfolio-scraper/{}\r\nformat: WARC File Format 1.1\r\n", env!("CARGO_PKG_VERSION"));
+        write_record(&mut file, "warcinfo", &new_record_id(), None, "application/warc-fields", info.as_bytes(), &[])?;
+    }
+
+    info!("Archiving every HTTP exchange to {}", path);
+
+    if WRITER.set(Mutex::new(file)).is_err() {
+        warn!("A WARC file is already being written, ignoring {}", path);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn is_recording() -> bool {
+    WRITER.get().is_some()
+}
+
+// Writes the response record followed by the request record that produced it.
+pub(crate) fn write_exchange(exchange: &Exchange) -> Result<()> {
+    let writer = match WRITER.get() {
+        Some(writer) => writer,
+        None         => return Ok(())
+    };
+
+    let target = exchange.url.as_str();
+    let path = match exchange.url.query() {
+        Some(query) => format!("{}?{}", exchange.url.path(), query),
+        None        => exchange.url.path().to_string()
+    };
+
+    let mut request = format!("{} {} HTTP/1.1\r\nHost: {}\r\n", exchange.method, path, exchange.url.host_str().unwrap_or_default()).into_bytes();
+    write_headers(&mut request, exchange.request_headers, false);
+    request.extend_from_slice(b"\r\n");
+
+    let mut response = format!("{:?} {} {}\r\n", exchange.version, exchange.status.as_u16(), exchange.status.canonical_reason().unwrap_or_default()).into_bytes();
+    write_headers(&mut response, exchange.response_headers, true);
+    response.extend_from_slice(b"\r\n");
+    response.extend_from_slice(exchange.body);
+
+    let response_id = new_record_id();
+    let concurrent_to = format!("WARC-Concurrent-To: {}\r\n", response_id);
+
+    let mut file = writer.lock().unwrap();
+    write_record(&mut file, "response", &response_id, Some(target), "application/http;msgtype=response", &response, &[])?;
+    write_record(&mut file, "request", &new_record_id(), Some(target), "application/http;msgtype=request", &request, concurrent_to.as_bytes())?;
+    file.flush()?;
+
+    Ok(())
+}
+
+// reqwest has already removed the chunked encoding, so the header would no longer describe the body.
+fn write_headers(block: &mut Vec<u8>, headers: &HeaderMap, skip_transfer_encoding: bool) {
+    for (name, value) in headers {
+        if skip_transfer_encoding && name == TRANSFER_ENCODING {
+            continue;
+        }
+
+        block.extend_from_slice(name.as_str().as_bytes());
+        block.extend_from_slice(b": ");
+        block.extend_from_slice(value.as_bytes());
+        block.extend_from_slice(b"\r\n");
+    }
+}
+
+fn write_record(file: &mut File, warc_type: &str, record_id: &str, target: Option<&str>, content_type: &str, block: &[u8], extra_headers: &[u8]) -> Result<()> {
+    let mut record = format!("WARC/1.1\r\nWARC-Type: {}\r\nWARC-Record-ID: {}\r\nWARC-Date: {}\r\n",
+                             warc_type, record_id, Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)).into_bytes();
+
+    if let Some(target) = target {
+        record.extend_from_slice(format!("WARC-Target-URI: {}\r\n", target).as_bytes());
+    }
+
+    record.extend_from_slice(extra_headers);
+    record.extend_from_slice(format!("Content-Type: {}\r\nContent-Length: {}\r\n\r\n", content_type, block.len()).as_bytes());
+    record.extend_from_slice(block);
+    record.extend_from_slice(b"\r\n\r\n");
+
+    file.write_all(&record)?;
+    Ok(())
+}
+
+fn new_record_id() -> String {
+    format!("<urn:uuid:{}>", Uuid::new_v4())
+}
+
+// Loads every response in the WARC file at `path`, so pages are served from it instead of the network.
+// When a URL was fetched more than once, the latest response wins.
+pub(crate) fn replay_from(path: &str) -> Result<()> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut responses = BTreeMap::new();
+
+    while let Some(headers) = read_record_headers(&mut reader)? {
+        let length = match headers.get("content-length").map(|length| length.parse::<usize>()) {
+            Some(Ok(length)) => length,
+            _                => return Err(archive_error(path, "record without a valid Content-Length"))
+        };
+
+        let mut block = vec![0; length];
+        reader.read_exact(&mut block)?;
+
+        let mut separator = [0; 4];
+        reader.read_exact(&mut separator)?;
+
+        if headers.get("warc-type").map(String::as_str) != Some("response") {
+            continue;
+        }
+
+        let target = match headers.get("warc-target-uri") {
+            Some(target) => target.clone(),
+            None         => return Err(archive_error(path, "response record without a WARC-Target-URI"))
+        };
+
+        match parse_response(&block) {
+            Some(replayed) => {
+                responses.insert(target, replayed);
+            },
+            None => warn!("Skipping unreadable archived response for {}", target)
+        }
+    }
+
+    info!("Replaying {} archived responses from {}", responses.len(), path);
+
+    if REPLAY.set(responses).is_err() {
+        warn!("A WARC file is already being replayed, ignoring {}", path);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn is_replaying() -> bool {
+    REPLAY.get().is_some()
+}
+
+// Looks a URL up the same way it was written, through `Url`'s normalisation.
+pub(crate) fn replayed(url: &str) -> Option<&'static Replayed> {
+    let url = Url::parse(url).ok()?;
+    REPLAY.get()?.get(url.as_str())
+}
+
+// Returns the record's headers, with lowercase names, or None at the end of the file.
+fn read_record_headers(reader: &mut impl BufRead) -> Result<Option<BTreeMap<String, String>>> {
+    let mut line = String::new();
+
+    // Tolerate stray blank lines between records.
+    loop {
+        line.clear();
+
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if !line.trim().is_empty() {
+            break;
+        }
+    }
+
+    if !line.starts_with("WARC/") {
+        return Err(Error::ArchiveError(format!("Expected a WARC record, found '{}'", line.trim())));
+    }
+
+    let mut headers = BTreeMap::new();
+
+    loop {
+        line.clear();
+
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    Ok(Some(headers))
+}
+
+fn parse_response(block: &[u8]) -> Option<Replayed> {
+    let split = block.windows(4).position(|window| window == b"\r\n\r\n")?;
+    let head = std::str::from_utf8(&block[..split]).ok()?;
+    let mut lines = head.lines();
+    let status = lines.next()?.split_whitespace().nth(1)?.parse::<u16>().ok()?;
+    let location = lines.filter_map(|line| line.split_once(':'))
+                        .find(|(name, _)| name.trim().eq_ignore_ascii_case("location"))
+                        .map(|(_, value)| value.trim().to_string());
+
+    Some(Replayed {
+        status: StatusCode::from_u16(status).ok()?,
+        location,
+        body: String::from_utf8_lossy(&block[split + 4..]).into_owned()
+    })
+}
+
+fn archive_error(path: &str, message: &str) -> Error {
+    Error::ArchiveError(format!("Unable to read WARC file {}: {}", path, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_response_keeps_the_location_of_redirects() {
+        let replayed = parse_response(b"HTTP/1.1 301 Moved Permanently\r\nlocation: /makes/\r\n\r\n").unwrap();
+
+        assert_eq!(replayed.status, StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(replayed.location.as_deref(), Some("/makes/"));
+        assert_eq!(replayed.body, "");
+    }
+
+    #[test]
+    fn parse_response_reads_the_body() {
+        let replayed = parse_response(b"HTTP/1.1 200 OK\r\ncontent-type: text/html\r\n\r\n<html></html>").unwrap();
+
+        assert_eq!(replayed.status, StatusCode::OK);
+        assert_eq!(replayed.location, None);
+        assert_eq!(replayed.body, "<html></html>");
+    }
+}